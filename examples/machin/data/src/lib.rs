@@ -12,3 +12,7 @@ impl Drop for MachinEnum {
         }
     }
 }
+
+/// Datum which is neither `Send` nor `Sync`.
+#[derive(Debug, Clone)]
+pub struct MachinRc(pub std::rc::Rc<String>);
@@ -1,120 +1,53 @@
-use std::{collections::BTreeMap, env, fs::File, io::Write, path::PathBuf};
+use std::{fs::File, io::Write};
 
-use machin_data::MachinEnum;
+use examples_target_types::register_example_types;
+use machin_data::{MachinEnum, MachinRc};
 use truc::{
+    build::BuildInfo,
     generator::{config::GeneratorConfig, generate},
     record::{
-        definition::builder::native::{DatumDefinitionOverride, NativeRecordDefinitionBuilder},
-        type_resolver::{DynamicTypeInfo, StaticTypeResolver},
+        definition::builder::native::NativeRecordDefinitionBuilder,
+        type_resolver::StaticTypeResolver,
     },
 };
 
 const SHARED_DIR: &str = "shared_truc_examples";
 
-enum CrossCompilation {
-    No,
-    Yes { shared_path: PathBuf },
-}
-
-struct BuildInfo {
-    out_dir_path: PathBuf,
-    cross_compilation: CrossCompilation,
-}
-
 fn get_build_info() -> BuildInfo {
-    let out_dir = env::var("OUT_DIR").expect("OUT_DIR");
-    let out_dir_path = PathBuf::from(out_dir);
-
-    let cross_compiling = std::env::var("TRUC_CROSS").is_ok();
-
-    let host = std::env::var("HOST").expect("HOST");
-    let target = std::env::var("TARGET").expect("TARGET");
-
-    if cross_compiling {
-        println!("Cross compiling for target {} on host {}", target, host);
-
-        let cargo_target_dir = std::env::var("CARGO_TARGET_DIR").expect("CARGO_TARGET_DIR");
-        let profile = std::env::var("PROFILE").expect("PROFILE");
-
-        let target_dir_path = PathBuf::from(cargo_target_dir);
-        assert!(std::fs::metadata(&target_dir_path).unwrap().is_dir());
-        let shared_path = target_dir_path.join(SHARED_DIR).join(target).join(profile);
-
-        BuildInfo {
-            cross_compilation: CrossCompilation::Yes { shared_path },
-            out_dir_path,
-        }
-    } else {
-        if host != target {
-            panic!(
-                "Cross compilation not detected for target {} on host {}",
-                target, host
-            );
-        } else {
-            println!("Compiling for target {}", target);
-        }
-
-        BuildInfo {
-            cross_compilation: CrossCompilation::No,
-            out_dir_path,
-        }
-    }
+    BuildInfo::from_env(SHARED_DIR).unwrap_or_else(|err| panic!("{}", err))
 }
 
-fn build_type_resolver(cross_compilation: &CrossCompilation) -> StaticTypeResolver {
-    match cross_compilation {
-        CrossCompilation::No => {
+fn build_type_resolver(build_info: &BuildInfo) -> StaticTypeResolver {
+    build_info
+        .type_resolver({
             let mut resolver = StaticTypeResolver::new();
             resolver.add_all_types();
-            resolver.add_type::<MachinEnum>();
+            register_example_types(&mut resolver);
             resolver
-        }
-        CrossCompilation::Yes { shared_path } => {
-            let file_path = shared_path.join("target_types.json");
-            let json = std::fs::read_to_string(&file_path)
-                .unwrap_or_else(|err| panic!("Could not read {:?}: {:?}", &file_path, err));
-            let target_types: BTreeMap<String, DynamicTypeInfo> = serde_json::from_str(&json)
-                .unwrap_or_else(|err| panic!("Could not parse {:?}: {:?}", &file_path, err));
-            StaticTypeResolver::from(target_types)
-        }
-    }
+        })
+        .unwrap_or_else(|err| panic!("{}", err))
 }
 
 fn machin() {
-    let BuildInfo {
-        out_dir_path,
-        cross_compilation,
-    } = get_build_info();
+    let build_info = get_build_info();
 
-    let type_resolver = build_type_resolver(&cross_compilation);
+    let type_resolver = build_type_resolver(&build_info);
 
     let mut definition = NativeRecordDefinitionBuilder::new(&type_resolver);
 
-    let a0 = definition
-        .add_datum_allow_uninit::<u32, _>("datum_a")
-        .unwrap();
-    let b0 = definition
-        .add_datum_allow_uninit::<u32, _>("datum_b")
-        .unwrap();
+    let a0 = definition.add_datum::<u32, _>("datum_a").unwrap();
+    let b0 = definition.add_datum::<u32, _>("datum_b").unwrap();
     definition.close_record_variant();
 
-    let c1 = definition
-        .add_datum_allow_uninit::<u32, _>("datum_c")
-        .unwrap();
+    let c1 = definition.add_datum::<u32, _>("datum_c").unwrap();
     definition.close_record_variant();
 
     definition.remove_datum(a0).unwrap();
     definition.close_record_variant();
 
-    let d3 = definition
-        .add_datum_allow_uninit::<u8, _>("datum_d")
-        .unwrap();
-    let e3 = definition
-        .add_datum_allow_uninit::<u16, _>("datum_e")
-        .unwrap();
-    let f3 = definition
-        .add_datum_allow_uninit::<u32, _>("datum_f")
-        .unwrap();
+    let d3 = definition.add_datum::<u8, _>("datum_d").unwrap();
+    let e3 = definition.add_datum::<u16, _>("datum_e").unwrap();
+    let f3 = definition.add_datum::<u32, _>("datum_f").unwrap();
     definition.close_record_variant();
     definition.close_record_variant();
 
@@ -135,7 +68,7 @@ fn machin() {
 
     let definition = definition.build();
 
-    let mut file = File::create(out_dir_path.join("machin_truc.rs")).unwrap();
+    let mut file = File::create(build_info.out_dir_path().join("machin_truc.rs")).unwrap();
     write!(
         file,
         "{}",
@@ -145,12 +78,9 @@ fn machin() {
 }
 
 fn index_first_char() {
-    let BuildInfo {
-        out_dir_path,
-        cross_compilation,
-    } = get_build_info();
+    let build_info = get_build_info();
 
-    let type_resolver = build_type_resolver(&cross_compilation);
+    let type_resolver = build_type_resolver(&build_info);
 
     let mut def_1 = NativeRecordDefinitionBuilder::new(&type_resolver);
 
@@ -170,20 +100,12 @@ fn index_first_char() {
 
     def_1.remove_datum(word).unwrap();
     def_1
-        .add_datum_override::<Vec<()>, _>(
-            "words",
-            DatumDefinitionOverride {
-                type_name: Some(format!("Vec<super::def_2::Record{}>", group)),
-                size: None,
-                align: None,
-                allow_uninit: None,
-            },
-        )
+        .add_dynamic_datum("words", format!("Vec<super::def_2::Record{}>", group))
         .unwrap();
     def_1.close_record_variant();
 
     let def_1 = def_1.build();
-    let mut file = File::create(out_dir_path.join("index_first_char_1.rs")).unwrap();
+    let mut file = File::create(build_info.out_dir_path().join("index_first_char_1.rs")).unwrap();
     write!(
         file,
         "{}",
@@ -195,7 +117,7 @@ fn index_first_char() {
     .unwrap();
 
     let def_2 = def_2.build();
-    let mut file = File::create(out_dir_path.join("index_first_char_2.rs")).unwrap();
+    let mut file = File::create(build_info.out_dir_path().join("index_first_char_2.rs")).unwrap();
     write!(
         file,
         "{}",
@@ -208,42 +130,23 @@ fn index_first_char() {
 }
 
 fn serialize_deserialize() {
-    let BuildInfo {
-        out_dir_path,
-        cross_compilation,
-    } = get_build_info();
+    let build_info = get_build_info();
 
-    let type_resolver = build_type_resolver(&cross_compilation);
+    let type_resolver = build_type_resolver(&build_info);
 
     let mut definition = NativeRecordDefinitionBuilder::new(&type_resolver);
 
-    let a0 = definition
-        .add_datum_allow_uninit::<u32, _>("datum_a")
-        .unwrap();
-    let b0 = definition
-        .add_datum_allow_uninit::<u32, _>("datum_b")
-        .unwrap();
+    let a0 = definition.add_datum::<u32, _>("datum_a").unwrap();
+    let b0 = definition.add_datum::<u32, _>("datum_b").unwrap();
     definition.close_record_variant();
 
-    let c1 = definition
-        .add_datum_allow_uninit::<u32, _>("datum_c")
-        .unwrap();
+    let c1 = definition.add_datum::<u32, _>("datum_c").unwrap();
     definition.close_record_variant();
 
     definition.remove_datum(a0).unwrap();
     definition.close_record_variant();
 
-    let v = definition
-        .add_datum_override::<Vec<()>, _>(
-            "datum_v",
-            DatumDefinitionOverride {
-                type_name: Some("Vec<u32>".to_string()),
-                size: None,
-                align: None,
-                allow_uninit: None,
-            },
-        )
-        .unwrap();
+    let v = definition.add_dynamic_datum("datum_v", "Vec<u32>").unwrap();
     definition.close_record_variant();
 
     definition.remove_datum(b0).unwrap();
@@ -253,7 +156,8 @@ fn serialize_deserialize() {
 
     let definition = definition.build();
 
-    let mut file = File::create(out_dir_path.join("serialize_deserialize.rs")).unwrap();
+    let mut file =
+        File::create(build_info.out_dir_path().join("serialize_deserialize.rs")).unwrap();
     write!(
         file,
         "{}",
@@ -265,8 +169,37 @@ fn serialize_deserialize() {
     .unwrap();
 }
 
+fn send_sync() {
+    let build_info = get_build_info();
+
+    let type_resolver = build_type_resolver(&build_info);
+
+    let mut definition = NativeRecordDefinitionBuilder::new(&type_resolver);
+
+    definition.add_datum::<u32, _>("datum_a").unwrap();
+    definition.add_datum::<String, _>("datum_b").unwrap();
+    definition.close_record_variant();
+
+    let rc = definition.add_datum::<MachinRc, _>("datum_rc").unwrap();
+    definition.close_record_variant();
+
+    definition.remove_datum(rc).unwrap();
+    definition.close_record_variant();
+
+    let definition = definition.build();
+
+    let mut file = File::create(build_info.out_dir_path().join("send_sync.rs")).unwrap();
+    write!(
+        file,
+        "{}",
+        generate(&definition, &GeneratorConfig::default())
+    )
+    .unwrap();
+}
+
 fn main() {
     machin();
     index_first_char();
     serialize_deserialize();
+    send_sync();
 }
@@ -156,7 +156,7 @@ fn serialize_deserialize_json() {
         datum_b: 2,
     });
 
-    let record_0_json = serde_json::to_value(&record_0).unwrap();
+    let record_0_json = serde_json::to_value(record_0).unwrap();
     assert_eq!(record_0_json, json!([1, 2]));
 
     for record_0 in [
@@ -173,7 +173,7 @@ fn serialize_deserialize_json() {
 
     let record_1 = Record1::from((record_0, UnpackedRecordIn1 { datum_c: 3 }));
 
-    let record_1_json = serde_json::to_value(&record_1).unwrap();
+    let record_1_json = serde_json::to_value(record_1).unwrap();
     assert_eq!(record_1_json, json!([1, 2, 3]));
 
     for record_1 in [
@@ -192,7 +192,7 @@ fn serialize_deserialize_json() {
 
     let record_2 = Record2::from((record_1, UnpackedRecordIn2 {}));
 
-    let record_2_json = serde_json::to_value(&record_2).unwrap();
+    let record_2_json = serde_json::to_value(record_2).unwrap();
     assert_eq!(record_2_json, json!([2, 3]));
 
     for record_2 in [
@@ -233,7 +233,7 @@ fn serialize_deserialize_json() {
 
     let record_4 = Record4::from((record_3, UnpackedRecordIn4 {}));
 
-    let record_4_json = serde_json::to_value(&record_4).unwrap();
+    let record_4_json = serde_json::to_value(record_4).unwrap();
     assert_eq!(record_4_json, json!([]));
 
     for record_4 in [
@@ -327,9 +327,113 @@ fn serialize_deserialize_bincode() {
     println!("serialize_deserialize_bincode OK");
 }
 
+fn send_sync() {
+    use std::rc::Rc;
+
+    use machin_data::MachinRc;
+
+    use crate::truc::send_sync::*;
+
+    // Records are `Send` and `Sync` only if all their datums are
+    assert_impl_all!(Record0: Send, Sync);
+    assert_not_impl_any!(Record1: Send);
+    assert_not_impl_any!(Record1: Sync);
+    assert_impl_all!(Record2: Send, Sync);
+
+    let record_0 = Record0::new(UnpackedRecord0 {
+        datum_a: 1,
+        datum_b: "two".to_string(),
+    });
+
+    let record_0 = std::thread::spawn(move || record_0).join().unwrap();
+
+    let rc = Rc::new("rc".to_string());
+    let record_1 = Record1::from((
+        record_0,
+        UnpackedRecordIn1 {
+            datum_rc: MachinRc(rc.clone()),
+        },
+    ));
+    assert_eq!(Rc::strong_count(&rc), 2);
+
+    let Record2AndUnpackedOut {
+        record: record_2,
+        datum_rc,
+    } = Record2AndUnpackedOut::from((record_1, UnpackedRecordIn2 {}));
+    drop(datum_rc);
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let record_2 = std::thread::spawn(move || record_2).join().unwrap();
+    assert_eq!(*record_2.datum_a(), 1);
+    assert_eq!(record_2.datum_b(), "two");
+
+    println!("send_sync OK");
+}
+
+fn variant_size() {
+    use std::rc::Rc;
+
+    use machin_data::MachinRc;
+
+    use crate::truc::send_sync::*;
+
+    assert!(Record0::VARIANT_SIZE < MAX_SIZE);
+    assert!(Record1::VARIANT_SIZE <= MAX_SIZE);
+    assert_eq!(Record0::SIZE_OF_DATUM_A, std::mem::size_of::<u32>());
+    assert_eq!(Record0::ALIGN_OF_DATUM_A, std::mem::align_of::<u32>());
+    assert_eq!(Record0::OFFSET_OF_DATUM_A, Record1::OFFSET_OF_DATUM_A);
+    assert!(Record1::OFFSET_OF_DATUM_RC + Record1::SIZE_OF_DATUM_RC <= Record1::VARIANT_SIZE);
+
+    // A record which is never converted may use a tighter capacity
+    type TightRecord0 = CappedRecord0<{ Record0::VARIANT_SIZE }>;
+    assert!(std::mem::size_of::<TightRecord0>() < std::mem::size_of::<Record0>());
+
+    let record_0 = TightRecord0::new(UnpackedRecord0 {
+        datum_a: 1,
+        datum_b: "two".to_string(),
+    });
+    assert_eq!(*record_0.datum_a(), 1);
+    assert_eq!(record_0.datum_b(), "two");
+
+    // It is resized once it needs to be converted
+    let record_0: Record0 = record_0.resize_cap();
+    assert_eq!(*record_0.datum_a(), 1);
+    assert_eq!(record_0.datum_b(), "two");
+    let record_1 = Record1::from((
+        record_0,
+        UnpackedRecordIn1 {
+            datum_rc: MachinRc(Rc::new("rc".to_string())),
+        },
+    ));
+    assert_eq!(record_1.datum_rc().0.as_str(), "rc");
+
+    println!("variant_size OK");
+}
+
+fn copy() {
+    use crate::truc::*;
+
+    // Records are `Copy` only if all their datums are
+    assert_impl_all!(Record0: Copy);
+    assert_not_impl_any!(Record4: Copy);
+
+    let record_0 = Record0::new(UnpackedRecord0 {
+        datum_a: 1,
+        datum_b: 2,
+    });
+    let copy_0 = record_0;
+    assert_eq!(*record_0.datum_a(), *copy_0.datum_a());
+    assert_eq!(*record_0.datum_b(), *copy_0.datum_b());
+
+    println!("copy OK");
+}
+
 fn main() -> Result<(), String> {
     machin();
     serialize_deserialize_json();
     serialize_deserialize_bincode();
+    send_sync();
+    variant_size();
+    copy();
     Ok(())
 }
@@ -13,3 +13,7 @@ pub mod index_first_char {
 pub mod serialize_deserialize {
     include!(concat!(env!("OUT_DIR"), "/serialize_deserialize.rs"));
 }
+
+pub mod send_sync {
+    include!(concat!(env!("OUT_DIR"), "/send_sync.rs"));
+}
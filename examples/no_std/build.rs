@@ -0,0 +1,41 @@
+use std::{env, fs::File, io::Write, path::PathBuf};
+
+use truc::{
+    generator::{config::GeneratorConfig, generate},
+    record::{
+        definition::builder::native::NativeRecordDefinitionBuilder, type_resolver::HostTypeResolver,
+    },
+};
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR");
+    let out_dir_path = PathBuf::from(out_dir);
+
+    // Those types have the same layout on every target, the host type resolver is then enough
+    // even when cross compiling
+    let mut definition = NativeRecordDefinitionBuilder::new(HostTypeResolver);
+
+    let timestamp_id = definition
+        .add_datum_allow_uninit::<u32, _>("timestamp")
+        .unwrap();
+    definition
+        .add_datum_allow_uninit::<u16, _>("raw_value")
+        .unwrap();
+    definition.close_record_variant();
+
+    definition.remove_datum(timestamp_id).unwrap();
+    definition
+        .add_datum_allow_uninit::<bool, _>("alert")
+        .unwrap();
+    definition.close_record_variant();
+
+    let definition = definition.build();
+
+    let mut file = File::create(out_dir_path.join("no_std_truc.rs")).unwrap();
+    write!(
+        file,
+        "{}",
+        generate(&definition, &GeneratorConfig::default().with_no_std())
+    )
+    .unwrap();
+}
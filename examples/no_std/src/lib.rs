@@ -0,0 +1,39 @@
+//! Records used in a `no_std` crate.
+//!
+//! The code is generated with [`GeneratorConfig::with_no_std`] and `truc_runtime` is used without
+//! its default features.
+//!
+//! [`GeneratorConfig::with_no_std`]: https://docs.rs/truc/latest/truc/generator/config/struct.GeneratorConfig.html#method.with_no_std
+
+#![no_std]
+
+#[allow(dead_code)]
+#[allow(clippy::borrowed_box)]
+#[allow(clippy::module_inception)]
+mod truc {
+    include!(concat!(env!("OUT_DIR"), "/no_std_truc.rs"));
+}
+
+pub use crate::truc::{Record0, Record1, UnpackedRecord0, UnpackedRecordIn1};
+
+/// Converts a sample to an alert record, raised when the value exceeds the threshold.
+pub fn check(sample: Record0, threshold: u16) -> Record1 {
+    let alert = *sample.raw_value() > threshold;
+    Record1::from((sample, UnpackedRecordIn1 { alert }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let sample = Record0::new(UnpackedRecord0 {
+            timestamp: 42,
+            raw_value: 1000,
+        });
+        let record = check(sample, 500);
+        assert_eq!(*record.raw_value(), 1000);
+        assert!(*record.alert());
+    }
+}
@@ -1,6 +1,3 @@
-#[macro_use]
-extern crate static_assertions;
-
 #[allow(dead_code)]
 #[allow(clippy::borrowed_box)]
 #[allow(clippy::module_inception)]
@@ -0,0 +1,9 @@
+//! Types of the examples, shared by their build scripts and the binary computing the types of a
+//! target.
+
+use machin_data::{MachinEnum, MachinRc};
+
+truc::register_types! {
+    /// Adds the types of the examples to a resolver.
+    pub fn register_example_types(MachinEnum, MachinRc);
+}
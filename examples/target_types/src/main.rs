@@ -1,25 +1,38 @@
 use std::io::Write;
 
 use clap::Parser;
-use machin_data::MachinEnum;
-use truc::record::type_resolver::StaticTypeResolver;
+use examples_target_types::register_example_types;
+use truc::{build::layout::LayoutProbe, record::type_resolver::StaticTypeResolver};
 
 #[derive(Parser, Debug)]
 struct Args {
     #[clap(short, long, value_parser, value_name = "OUTPUT")]
     output: Option<String>,
+    /// Computes the types of TARGET on the host instead of running on the target
+    #[clap(short, long, value_parser, value_name = "TARGET")]
+    target: Option<String>,
 }
 
 fn main() {
     let args = Args::parse();
 
-    let mut type_infos = StaticTypeResolver::new();
-
-    type_infos.add_all_types();
-
-    type_infos.add_type::<MachinEnum>();
-
-    let content = type_infos.to_json_string_pretty().unwrap();
+    let content = if let Some(target) = args.target {
+        let mut host_resolver = StaticTypeResolver::new();
+        host_resolver.add_all_types();
+        register_example_types(&mut host_resolver);
+        let target_resolver =
+            LayoutProbe::new(std::env::temp_dir().join(format!("truc_layout_probe_{}", target)))
+                .with_target(target)
+                .with_dependency(format!(
+                    "machin_data = {{ path = {:?} }}",
+                    concat!(env!("CARGO_MANIFEST_DIR"), "/../machin/data")
+                ))
+                .type_resolver(&host_resolver)
+                .unwrap();
+        target_resolver.to_json_string_pretty().unwrap()
+    } else {
+        StaticTypeResolver::target_types_json(register_example_types).unwrap()
+    };
     if let Some(output) = args.output {
         let mut output = std::fs::File::create(output).unwrap();
         writeln!(output, "{}", content).unwrap();
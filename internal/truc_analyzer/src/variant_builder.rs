@@ -4,7 +4,10 @@ use average::MeanWithError;
 use rand::Rng;
 use rand_chacha::rand_core::SeedableRng;
 use truc::record::{
-    definition::{builder::native::NativeRecordDefinitionBuilder, DatumId},
+    definition::{
+        builder::native::{NativeDatumError, NativeRecordDefinitionBuilder},
+        DatumId,
+    },
     type_resolver::{HostTypeResolver, TypeResolver},
 };
 
@@ -98,7 +101,7 @@ fn add_one_datum<R: TypeResolver>(
     definition: &mut NativeRecordDefinitionBuilder<R>,
     rng: &mut rand_chacha::ChaCha8Rng,
     i: usize,
-) -> Result<DatumId, String> {
+) -> Result<DatumId, NativeDatumError> {
     match rng.gen_range(0..4) {
         0 => definition.add_datum_allow_uninit::<u8, _>(format!("field_{}", i)),
         1 => definition.add_datum_allow_uninit::<u16, _>(format!("field_{}", i)),
@@ -0,0 +1,567 @@
+use std::{env, fmt::Write as _, fs::File, io::Write, path::PathBuf};
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use truc::{
+    generator::{config::GeneratorConfig, generate},
+    record::{
+        definition::builder::native::{DatumDefinitionOverride, NativeRecordDefinitionBuilder},
+        type_resolver::{StaticTypeResolver, TypeResolver},
+    },
+};
+
+const DEFAULT_SEED: u64 = 0x7472_7563_5f73_6166;
+const DEFAULT_DEFINITIONS: usize = 16;
+
+const MAX_VARIANTS: usize = 4;
+const MAX_INITIAL_DATA: usize = 10;
+const MAX_PLUS_DATA: usize = 4;
+const VEC_LEN: usize = 8;
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    U8,
+    U16,
+    U32,
+    U64,
+    String,
+    BoxStr,
+    VecUsize,
+    Tracked,
+}
+
+const KINDS: [Kind; 8] = [
+    Kind::U8,
+    Kind::U16,
+    Kind::U32,
+    Kind::U64,
+    Kind::String,
+    Kind::BoxStr,
+    Kind::VecUsize,
+    Kind::Tracked,
+];
+
+impl Kind {
+    fn add<R: TypeResolver>(
+        self,
+        definition: &mut NativeRecordDefinitionBuilder<R>,
+        name: &str,
+    ) -> truc::record::definition::DatumId {
+        match self {
+            Kind::U8 => definition.add_datum_allow_uninit::<u8, _>(name),
+            Kind::U16 => definition.add_datum_allow_uninit::<u16, _>(name),
+            Kind::U32 => definition.add_datum_allow_uninit::<u32, _>(name),
+            Kind::U64 => definition.add_datum_allow_uninit::<u64, _>(name),
+            Kind::String => definition.add_datum::<String, _>(name),
+            Kind::BoxStr => definition.add_dynamic_datum(name, "Box<str>"),
+            Kind::VecUsize => definition.add_datum_override::<Vec<()>, _>(
+                name,
+                DatumDefinitionOverride {
+                    type_name: Some("Vec<usize>".to_owned()),
+                    size: None,
+                    align: None,
+                    needs_drop: None,
+                    allow_uninit: None,
+                },
+            ),
+            // Tracked is a transparent wrapper of Box<u64>
+            Kind::Tracked => definition.add_datum_override::<Box<u64>, _>(
+                name,
+                DatumDefinitionOverride {
+                    type_name: Some("crate::Tracked".to_owned()),
+                    size: None,
+                    align: None,
+                    needs_drop: None,
+                    allow_uninit: None,
+                },
+            ),
+        }
+        .unwrap()
+    }
+
+    fn allow_uninit(self) -> bool {
+        matches!(self, Kind::U8 | Kind::U16 | Kind::U32 | Kind::U64)
+    }
+
+    /// Rust expression of a value of that kind, derived from `n`.
+    fn value(self, n: &Number) -> String {
+        match (self, n) {
+            (Kind::U8, Number::Literal(n)) => format!("{}_u8", n % 0x100),
+            (Kind::U16, Number::Literal(n)) => format!("{}_u16", n % 0x10000),
+            (Kind::U32, Number::Literal(n)) => format!("{}_u32", n),
+            (Kind::U64, Number::Literal(n)) => format!("{}_u64", n),
+            (Kind::U8, Number::Expr(n)) => format!("({}) as u8", n),
+            (Kind::U16, Number::Expr(n)) => format!("({}) as u16", n),
+            (Kind::U32, Number::Expr(n)) => format!("({}) as u32", n),
+            (Kind::U64, Number::Expr(n)) => format!("({}) as u64", n),
+            (Kind::String, n) => format!("format!(\"s{{}}\", {})", n),
+            (Kind::BoxStr, n) => format!("Box::<str>::from(format!(\"b{{}}\", {}))", n),
+            (Kind::VecUsize, n) => format!("vec![{}, 42]", n),
+            (Kind::Tracked, Number::Literal(n)) => format!("crate::Tracked::new({})", n),
+            (Kind::Tracked, Number::Expr(n)) => format!("crate::Tracked::new(({}) as u64)", n),
+        }
+    }
+}
+
+/// `usize` number, either known at build time or given by an expression.
+enum Number {
+    Literal(usize),
+    Expr(String),
+}
+
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Literal(n) => write!(f, "{}", n),
+            Number::Expr(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// Identifies an instance of the data: a literal or the `index` variable.
+#[derive(Clone, Copy)]
+enum Salt {
+    Literal(usize),
+    Index,
+}
+
+#[derive(Clone, Debug)]
+struct Datum {
+    index: usize,
+    name: String,
+    kind: Kind,
+}
+
+impl Datum {
+    /// Value of the datum in a given instance.
+    fn value(&self, salt: Salt) -> String {
+        self.kind.value(&match salt {
+            Salt::Literal(salt) => Number::Literal(salt * 100 + self.index),
+            Salt::Index if self.index == 0 => Number::Expr("index * 100".to_owned()),
+            Salt::Index => Number::Expr(format!("index * 100 + {}", self.index)),
+        })
+    }
+}
+
+struct Variant {
+    data: Vec<Datum>,
+    minus_data: Vec<Datum>,
+    plus_data: Vec<Datum>,
+    with_out: bool,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=TRUC_SOUNDNESS_SEED");
+    println!("cargo:rerun-if-env-changed=TRUC_SOUNDNESS_DEFINITIONS");
+    println!("cargo:rerun-if-env-changed=TRUC_CROSS");
+
+    let seed = env::var("TRUC_SOUNDNESS_SEED")
+        .ok()
+        .map(|seed| {
+            seed.parse::<u64>()
+                .unwrap_or_else(|err| panic!("Invalid TRUC_SOUNDNESS_SEED {}: {}", seed, err))
+        })
+        .unwrap_or(DEFAULT_SEED);
+    let definitions = env::var("TRUC_SOUNDNESS_DEFINITIONS")
+        .ok()
+        .map(|definitions| {
+            definitions.parse::<usize>().unwrap_or_else(|err| {
+                panic!(
+                    "Invalid TRUC_SOUNDNESS_DEFINITIONS {}: {}",
+                    definitions, err
+                )
+            })
+        })
+        .unwrap_or(DEFAULT_DEFINITIONS);
+
+    let out_dir_path = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR"));
+
+    // Types are resolved on the host, which only makes sense when not cross compiling
+    if env::var("TRUC_CROSS").is_ok() {
+        println!("cargo:warning=Cross compiling, soundness tests are not generated");
+        let mut file = File::create(out_dir_path.join("random_definitions.rs")).unwrap();
+        writeln!(
+            file,
+            "// Cross compiling, soundness tests are not generated"
+        )
+        .unwrap();
+        return;
+    }
+
+    let type_resolver = {
+        let mut resolver = StaticTypeResolver::new();
+        resolver.add_all_types();
+        resolver.add_type::<Box<u64>>();
+        resolver
+    };
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    let mut tests = String::new();
+    writeln!(tests, "// Seed: {}", seed).unwrap();
+    for def in 0..definitions {
+        let (code, variants) = random_definition(&type_resolver, &mut rng);
+        let file_name = format!("definition_{}.rs", def);
+        let mut file = File::create(out_dir_path.join(&file_name)).unwrap();
+        write!(file, "{}", code).unwrap();
+
+        writeln!(
+            tests,
+            r#"
+#[allow(dead_code)]
+#[allow(clippy::borrowed_box)]
+mod definition_{def} {{
+    include!(concat!(env!("OUT_DIR"), "/{file_name}"));
+}}
+"#,
+            def = def,
+            file_name = file_name
+        )
+        .unwrap();
+        generate_test(def, &variants, &mut tests);
+    }
+
+    let mut file = File::create(out_dir_path.join("random_definitions.rs")).unwrap();
+    write!(file, "{}", tests).unwrap();
+}
+
+fn random_definition<R: TypeResolver>(
+    type_resolver: R,
+    rng: &mut ChaCha8Rng,
+) -> (String, Vec<Variant>) {
+    let mut definition = NativeRecordDefinitionBuilder::new(type_resolver);
+    let mut next_index = 0;
+    let mut add_one = |definition: &mut NativeRecordDefinitionBuilder<R>, rng: &mut ChaCha8Rng| {
+        let kind = *KINDS.choose(rng).unwrap();
+        let index = next_index;
+        next_index += 1;
+        let name = format!("datum_{}", index);
+        let id = kind.add(definition, &name);
+        (id, Datum { index, name, kind })
+    };
+
+    let mut data = Vec::new();
+    let mut variants = Vec::<Variant>::new();
+    let num_variants = rng.gen_range(1..=MAX_VARIANTS);
+    for variant in 0..num_variants {
+        let mut minus_data = Vec::new();
+        if variant > 0 {
+            data.retain(|(id, datum): &(_, Datum)| {
+                if rng.gen_ratio(1, 3) {
+                    definition.remove_datum(*id).unwrap();
+                    minus_data.push(datum.clone());
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        let num_plus = rng.gen_range(
+            0..=if variant == 0 {
+                MAX_INITIAL_DATA
+            } else {
+                MAX_PLUS_DATA
+            },
+        );
+        let plus_data: Vec<Datum> = (0..num_plus)
+            .map(|_| {
+                let (id, datum) = add_one(&mut definition, rng);
+                data.push((id, datum.clone()));
+                datum
+            })
+            .collect();
+        let variant_id = definition.close_record_variant();
+        if variant_id != variants.len().into() {
+            // Nothing changed, no new variant
+            continue;
+        }
+        variants.push(Variant {
+            data: data.iter().map(|(_, datum)| datum.clone()).collect(),
+            minus_data,
+            plus_data,
+            with_out: rng.gen(),
+        });
+    }
+
+    let definition = definition.build();
+    let code = generate(
+        &definition,
+        &GeneratorConfig::default()
+            .with_clone_fragments()
+            .with_serde_fragments(),
+    );
+    (code, variants)
+}
+
+fn struct_literal<'a>(name: &str, data: impl IntoIterator<Item = &'a Datum>, salt: Salt) -> String {
+    format!(
+        "{} {{ {} }}",
+        name,
+        data.into_iter()
+            .map(|datum| format!("{}: {}", datum.name, datum.value(salt)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn assert_data(out: &mut String, record: &str, data: &[Datum], salt: Salt) {
+    for datum in data {
+        writeln!(
+            out,
+            "        assert_eq!(*{}.{}(), {});",
+            record,
+            datum.name,
+            datum.value(salt)
+        )
+        .unwrap();
+    }
+}
+
+fn generate_test(def: usize, variants: &[Variant], out: &mut String) {
+    writeln!(
+        out,
+        r#"#[test]
+#[allow(
+    unused_mut,
+    unused_variables,
+    dropping_copy_types,
+    clippy::clone_on_copy,
+    clippy::drop_non_drop
+)]
+fn definition_{def}_life_cycle() {{
+    use definition_{def}::*;
+    {{"#,
+        def = def
+    )
+    .unwrap();
+
+    let first = &variants[0];
+
+    // Constructor, accessors and mutable accessors
+    writeln!(
+        out,
+        "        let mut record_0 = Record0::new({});",
+        struct_literal("UnpackedRecord0", &first.data, Salt::Literal(0))
+    )
+    .unwrap();
+    assert_data(out, "record_0", &first.data, Salt::Literal(0));
+    for datum in &first.data {
+        writeln!(
+            out,
+            "        *record_0.{}_mut() = {};",
+            datum.name,
+            datum.value(Salt::Literal(1))
+        )
+        .unwrap();
+    }
+    assert_data(out, "record_0", &first.data, Salt::Literal(1));
+
+    // Capacity resizes
+    writeln!(
+        out,
+        "        let tight_0 = record_0.resize_cap::<{{ Record0::VARIANT_SIZE }}>();"
+    )
+    .unwrap();
+    assert_data(out, "tight_0", &first.data, Salt::Literal(1));
+    writeln!(out, "        let record_0: Record0 = tight_0.resize_cap();").unwrap();
+    assert_data(out, "record_0", &first.data, Salt::Literal(1));
+
+    // Constructor without uninitialized data
+    writeln!(
+        out,
+        "        let mut uninit_0 = Record0::from({});",
+        struct_literal(
+            "UnpackedUninitRecord0",
+            first.data.iter().filter(|datum| !datum.kind.allow_uninit()),
+            Salt::Literal(2)
+        )
+    )
+    .unwrap();
+    for datum in first.data.iter().filter(|datum| datum.kind.allow_uninit()) {
+        writeln!(
+            out,
+            "        *uninit_0.{}_mut() = {};",
+            datum.name,
+            datum.value(Salt::Literal(2))
+        )
+        .unwrap();
+    }
+    assert_data(out, "uninit_0", &first.data, Salt::Literal(2));
+
+    // Clone and serde
+    writeln!(out, "        let clone_0 = record_0.clone();").unwrap();
+    assert_data(out, "clone_0", &first.data, Salt::Literal(1));
+    writeln!(
+        out,
+        "        let json = serde_json::to_string(&clone_0).unwrap();
+        drop(clone_0);
+        let deserialized_0 = serde_json::from_str::<Record0>(&json).unwrap();"
+    )
+    .unwrap();
+    assert_data(out, "deserialized_0", &first.data, Salt::Literal(1));
+    writeln!(
+        out,
+        "        drop(deserialized_0);\n        drop(uninit_0);"
+    )
+    .unwrap();
+
+    // Conversions
+    for (v, variant) in variants.iter().enumerate().skip(1) {
+        if variant.with_out {
+            writeln!(
+                out,
+                "        let Record{v}AndUnpackedOut {{ record: record_{v}{out_data} }} = Record{v}AndUnpackedOut::from((record_{prev}, {plus}));",
+                v = v,
+                prev = v - 1,
+                out_data = variant
+                    .minus_data
+                    .iter()
+                    .map(|datum| format!(", {}", datum.name))
+                    .collect::<String>(),
+                plus = struct_literal(&format!("UnpackedRecordIn{}", v), &variant.plus_data, Salt::Literal(1)),
+            )
+            .unwrap();
+            for datum in &variant.minus_data {
+                writeln!(
+                    out,
+                    "        assert_eq!({}, {});",
+                    datum.name,
+                    datum.value(Salt::Literal(1))
+                )
+                .unwrap();
+            }
+        } else {
+            writeln!(
+                out,
+                "        let record_{v} = Record{v}::from((record_{prev}, {plus}));",
+                v = v,
+                prev = v - 1,
+                plus = struct_literal(
+                    &format!("UnpackedRecordIn{}", v),
+                    &variant.plus_data,
+                    Salt::Literal(1)
+                ),
+            )
+            .unwrap();
+        }
+        assert_data(
+            out,
+            &format!("record_{}", v),
+            &variant.data,
+            Salt::Literal(1),
+        );
+    }
+
+    // Unpack
+    let last = variants.len() - 1;
+    writeln!(
+        out,
+        "        let UnpackedRecord{last} {{ {fields} }} = record_{last}.unpack();",
+        last = last,
+        fields = variants[last]
+            .data
+            .iter()
+            .map(|datum| datum.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+    .unwrap();
+    for datum in &variants[last].data {
+        writeln!(
+            out,
+            "        assert_eq!({}, {});",
+            datum.name,
+            datum.value(Salt::Literal(1))
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        r#"    }}
+    assert_eq!(crate::Tracked::live(), 0);
+}}
+"#
+    )
+    .unwrap();
+
+    for (v, variant) in variants.iter().enumerate().skip(1) {
+        generate_in_place_test(def, v, &variants[v - 1], variant, out);
+    }
+}
+
+fn generate_in_place_test(
+    def: usize,
+    v: usize,
+    prev: &Variant,
+    variant: &Variant,
+    out: &mut String,
+) {
+    let prev_v = v - 1;
+    writeln!(
+        out,
+        r#"#[test]
+#[allow(unused_mut, unused_variables)]
+fn definition_{def}_convert_vec_in_place_{prev_v}_to_{v}() {{
+    use definition_{def}::*;
+    use truc_runtime::convert::{{
+        convert_vec_in_place_indexed, try_convert_vec_in_place_indexed, VecElementConversionResult,
+    }};
+    {{
+        let records = (0..{len}).map(|index| Record{prev_v}::new({prev_literal})).collect::<Vec<_>>();
+        let converted = convert_vec_in_place_indexed::<Record{prev_v}, Record{v}, _>(records, |index, record, _| {{
+            if index % 3 == 2 {{
+                VecElementConversionResult::Abandonned
+            }} else {{
+                VecElementConversionResult::Converted(Record{v}::from((record, {plus_literal})))
+            }}
+        }});
+        assert_eq!(converted.len(), (0..{len}).filter(|index| index % 3 != 2).count());
+        for (record, index) in converted.iter().zip((0..{len}).filter(|index| index % 3 != 2)) {{"#,
+        def = def,
+        prev_v = prev_v,
+        v = v,
+        len = VEC_LEN,
+        prev_literal = struct_literal(&format!("UnpackedRecord{}", prev_v), &prev.data, Salt::Index),
+        plus_literal = struct_literal(&format!("UnpackedRecordIn{}", v), &variant.plus_data, Salt::Index),
+    )
+    .unwrap();
+    for datum in &variant.data {
+        writeln!(
+            out,
+            "            assert_eq!(*record.{}(), {});",
+            datum.name,
+            datum.value(Salt::Index)
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        r#"        }}
+    }}
+    assert_eq!(crate::Tracked::live(), 0);
+    {{
+        let records = (0..{len}).map(|index| Record{prev_v}::new({prev_literal})).collect::<Vec<_>>();
+        let result = try_convert_vec_in_place_indexed::<Record{prev_v}, Record{v}, _, _>(records, |index, record, _| {{
+            if index == {len} / 2 {{
+                Err(index)
+            }} else if index % 3 == 2 {{
+                Ok(VecElementConversionResult::Abandonned)
+            }} else {{
+                Ok(VecElementConversionResult::Converted(Record{v}::from((record, {plus_literal}))))
+            }}
+        }});
+        assert!(matches!(result, Err(index) if index == {len} / 2));
+    }}
+    assert_eq!(crate::Tracked::live(), 0);
+}}
+"#,
+        prev_v = prev_v,
+        v = v,
+        len = VEC_LEN,
+        prev_literal = struct_literal(&format!("UnpackedRecord{}", prev_v), &prev.data, Salt::Index),
+        plus_literal = struct_literal(&format!("UnpackedRecordIn{}", v), &variant.plus_data, Salt::Index),
+    )
+    .unwrap();
+}
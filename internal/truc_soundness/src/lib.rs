@@ -0,0 +1,72 @@
+//! Soundness tests of the generated code.
+//!
+//! The build script generates random record definitions along with tests exercising constructors,
+//! accessors, conversions, clone, serde and in place vector conversions. They are meant to be run
+//! under Miri (see the `miri` recipe of the justfile) or sanitizers, and they also check that no
+//! datum is leaked or dropped twice thanks to [Tracked] datums.
+//!
+//! The random generation is controlled by the following environment variables:
+//!
+//! * `TRUC_SOUNDNESS_SEED`: seed of the random generator
+//! * `TRUC_SOUNDNESS_DEFINITIONS`: number of definitions to generate
+
+#![cfg_attr(coverage_nightly, feature(coverage_attribute))]
+
+use std::cell::Cell;
+
+use serde::{Deserialize, Serialize};
+
+thread_local! {
+    static LIVE: Cell<isize> = Cell::new(0);
+}
+
+/// Datum counting its live instances in the current thread.
+///
+/// It owns heap memory so that leaks and double drops are also reported by Miri and sanitizers.
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub struct Tracked(Box<u64>);
+
+impl Tracked {
+    /// Constructs a new tracked value.
+    pub fn new(value: u64) -> Self {
+        LIVE.with(|live| live.set(live.get() + 1));
+        Self(Box::new(value))
+    }
+
+    /// Gets the number of live instances in the current thread.
+    pub fn live() -> isize {
+        LIVE.with(|live| live.get())
+    }
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        Self::new(*self.0)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        LIVE.with(|live| live.set(live.get() - 1));
+    }
+}
+
+impl From<u64> for Tracked {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Tracked> for u64 {
+    fn from(tracked: Tracked) -> Self {
+        *tracked.0
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    include!(concat!(env!("OUT_DIR"), "/random_definitions.rs"));
+}
@@ -0,0 +1,15 @@
+use std::{env, process::Command};
+
+fn main() {
+    // Recorded in the header of type information files, see `TypesHeader::current`
+    let target = env::var("TARGET").expect("TARGET");
+    println!("cargo:rustc-env=TRUC_TARGET={}", target);
+
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc).arg("-V").output().expect("rustc -V");
+    let rustc_version = String::from_utf8(output.stdout).expect("rustc version");
+    println!(
+        "cargo:rustc-env=TRUC_RUSTC_VERSION={}",
+        rustc_version.trim()
+    );
+}
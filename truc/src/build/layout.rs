@@ -0,0 +1,481 @@
+//! Type layouts of the target computed without running code on the target.
+//!
+//! A probe crate is generated with one pair of statics per type, the length of each static
+//! giving either the size or the alignment of the type:
+//!
+//! ```rust,ignore
+//! #[no_mangle]
+//! pub static TRUC_SIZE_0: [u8; core::mem::size_of::<String>()] = [0; core::mem::size_of::<String>()];
+//! ```
+//!
+//! The probe crate is compiled for the target by `cargo` with `--emit=llvm-ir`, and the lengths
+//! are read back from the emitted IR. Only the target standard library needs to be installed
+//! (`rustup target add <target>`), no linker nor emulator is required.
+//!
+//! Whether a type needs to be dropped and whether it can be left uninitialized do not depend on
+//! the target, they are taken from the host resolver.
+//!
+//! The resulting resolver has a header recording the target, its pointer width and the version of
+//! the compiler.
+//!
+//! ```rust,no_run
+//! use truc::{build::layout::LayoutProbe, record::type_resolver::StaticTypeResolver};
+//!
+//! let mut host_resolver = StaticTypeResolver::new();
+//! host_resolver.add_all_types();
+//!
+//! let target_resolver = LayoutProbe::new("target/truc_layout_probe")
+//!     .with_target("aarch64-unknown-linux-gnu")
+//!     .type_resolver(&host_resolver)
+//!     .unwrap();
+//!
+//! std::fs::write(
+//!     "target_types.json",
+//!     target_resolver.to_json_string_pretty().unwrap(),
+//! )
+//! .unwrap();
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    fmt::{Display, Formatter, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+};
+
+use super::rustc_info;
+use crate::record::type_resolver::{
+    DynamicTypeInfo, StaticTypeResolver, TypeInfo, TypeResolver, TypesHeader,
+};
+
+const PROBE_CRATE: &str = "truc_layout_probe";
+const SIZE_PREFIX: &str = "TRUC_SIZE_";
+const ALIGN_PREFIX: &str = "TRUC_ALIGN_";
+
+/// Errors of the layout probe.
+#[derive(Debug)]
+pub enum LayoutProbeError {
+    /// A file of the probe crate could not be written or read.
+    Io {
+        /// Path of the file.
+        path: PathBuf,
+        /// Cause of the error.
+        error: std::io::Error,
+    },
+    /// `rustc` could not be run to get its version and host triple.
+    Rustc(std::io::Error),
+    /// `cargo` could not be run or the probe crate could not be compiled.
+    Compile {
+        /// Exit status of `cargo`, if it could be run at all.
+        status: Option<ExitStatus>,
+        /// Error output of `cargo`.
+        stderr: String,
+    },
+    /// The LLVM IR emitted for the probe crate could not be found.
+    MissingIr {
+        /// Directory where the LLVM IR was expected.
+        dir: PathBuf,
+    },
+    /// A line of the LLVM IR could not be understood.
+    Parse {
+        /// The offending line.
+        line: String,
+    },
+    /// The layout of a type is missing from the LLVM IR.
+    MissingLayout {
+        /// Name of the type.
+        type_name: String,
+    },
+}
+
+impl Display for LayoutProbeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "I/O error on {:?}: {}", path, error),
+            Self::Rustc(error) => write!(f, "Could not get the rustc version: {}", error),
+            Self::Compile {
+                status: Some(status),
+                stderr,
+            } => write!(
+                f,
+                "Could not compile the layout probe ({}):\n{}",
+                status, stderr
+            ),
+            Self::Compile {
+                status: None,
+                stderr,
+            } => write!(f, "Could not run cargo: {}", stderr),
+            Self::MissingIr { dir } => {
+                write!(f, "Could not find the layout probe LLVM IR in {:?}", dir)
+            }
+            Self::Parse { line } => write!(f, "Could not parse layout probe line {:?}", line),
+            Self::MissingLayout { type_name } => {
+                write!(f, "Layout of type {} is missing", type_name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LayoutProbeError {}
+
+/// Computes the layouts of types for a target by compiling a probe crate.
+#[derive(Debug)]
+pub struct LayoutProbe {
+    work_dir: PathBuf,
+    target: Option<String>,
+    dependencies: Vec<String>,
+}
+
+impl LayoutProbe {
+    /// Creates a probe for the host, the probe crate being generated and compiled in `work_dir`.
+    pub fn new(work_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            work_dir: work_dir.into(),
+            target: None,
+            dependencies: Vec::new(),
+        }
+    }
+
+    /// Sets the target triple.
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Adds a dependency to the probe crate, as a line of the `[dependencies]` section of its
+    /// `Cargo.toml`, e.g. `my_types = { path = "/path/to/my_types" }`.
+    ///
+    /// Crates defining user types must be added so that the types can be named by the probe.
+    pub fn with_dependency(mut self, dependency: impl Into<String>) -> Self {
+        self.dependencies.push(dependency.into());
+        self
+    }
+
+    /// Gives the type resolver of the target for all the types of `host_resolver`.
+    pub fn type_resolver(
+        &self,
+        host_resolver: &StaticTypeResolver,
+    ) -> Result<StaticTypeResolver, LayoutProbeError> {
+        let type_names = host_resolver
+            .type_names()
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+
+        // The pointer width of the target is given by the size of `usize`.
+        let mut probed_type_names = type_names.clone();
+        probed_type_names.push("usize".to_owned());
+
+        let ir = self.compile(&probed_type_names)?;
+        let mut layouts = parse_layouts(&ir, &probed_type_names)?;
+        let (usize_size, _) = layouts.pop().expect("usize layout");
+
+        let types = type_names
+            .into_iter()
+            .zip(layouts)
+            .map(|(type_name, (size, align))| {
+                let host_info = host_resolver.dynamic_type_info(&type_name);
+                let info = DynamicTypeInfo {
+                    info: TypeInfo {
+                        name: type_name.clone(),
+                        size,
+                        align,
+                        needs_drop: host_info.info.needs_drop,
+                    },
+                    allow_uninit: host_info.allow_uninit,
+                };
+                (type_name, info)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let rustc = rustc_info(&env::var_os("RUSTC").unwrap_or_else(|| OsString::from("rustc")))
+            .map_err(LayoutProbeError::Rustc)?;
+
+        let mut resolver = StaticTypeResolver::from(types);
+        resolver.set_header(TypesHeader {
+            target: self.target.clone().unwrap_or(rustc.host),
+            pointer_width: (usize_size * 8) as u32,
+            rustc_version: rustc.version,
+        });
+        Ok(resolver)
+    }
+
+    fn compile(&self, type_names: &[String]) -> Result<String, LayoutProbeError> {
+        let src_dir = self.work_dir.join("src");
+        create_dir_all(&src_dir)?;
+        write(
+            &self.work_dir.join("Cargo.toml"),
+            &probe_manifest(&self.dependencies),
+        )?;
+        write(&src_dir.join("lib.rs"), &probe_source(type_names))?;
+
+        let target_dir = self.work_dir.join("target");
+        let mut command =
+            Command::new(env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo")));
+        command
+            .current_dir(&self.work_dir)
+            .env("CARGO_TARGET_DIR", &target_dir)
+            .args(["rustc", "--lib", "--release"]);
+        if let Some(target) = &self.target {
+            command.args(["--target", target]);
+        }
+        command.args(["--", "--emit=llvm-ir"]);
+        let output = command
+            .output()
+            .map_err(|error| LayoutProbeError::Compile {
+                status: None,
+                stderr: error.to_string(),
+            })?;
+        if !output.status.success() {
+            return Err(LayoutProbeError::Compile {
+                status: Some(output.status),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
+
+        let deps_dir = match &self.target {
+            Some(target) => target_dir.join(target),
+            None => target_dir,
+        }
+        .join("release")
+        .join("deps");
+        let ir_path = find_ir(&deps_dir)?;
+        std::fs::read_to_string(&ir_path).map_err(|error| LayoutProbeError::Io {
+            path: ir_path,
+            error,
+        })
+    }
+}
+
+fn create_dir_all(path: &Path) -> Result<(), LayoutProbeError> {
+    std::fs::create_dir_all(path).map_err(|error| LayoutProbeError::Io {
+        path: path.to_owned(),
+        error,
+    })
+}
+
+fn write(path: &Path, content: &str) -> Result<(), LayoutProbeError> {
+    std::fs::write(path, content).map_err(|error| LayoutProbeError::Io {
+        path: path.to_owned(),
+        error,
+    })
+}
+
+fn probe_manifest(dependencies: &[String]) -> String {
+    let mut manifest = format!(
+        "[package]\nname = \"{}\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n[workspace]\n\n[dependencies]\n",
+        PROBE_CRATE
+    );
+    for dependency in dependencies {
+        writeln!(manifest, "{}", dependency).expect("write");
+    }
+    manifest
+}
+
+fn probe_source(type_names: &[String]) -> String {
+    let mut source = String::from("// Generated by truc, do not edit.\n");
+    for (index, type_name) in type_names.iter().enumerate() {
+        for (prefix, function) in [(SIZE_PREFIX, "size_of"), (ALIGN_PREFIX, "align_of")] {
+            writeln!(
+                source,
+                "\n#[no_mangle]\npub static {prefix}{index}: [u8; core::mem::{function}::<{type_name}>()] = [0; core::mem::{function}::<{type_name}>()];",
+                prefix = prefix,
+                index = index,
+                function = function,
+                type_name = type_name,
+            )
+            .expect("write");
+        }
+    }
+    source
+}
+
+fn find_ir(deps_dir: &Path) -> Result<PathBuf, LayoutProbeError> {
+    let missing_ir = || LayoutProbeError::MissingIr {
+        dir: deps_dir.to_owned(),
+    };
+    let entries = std::fs::read_dir(deps_dir).map_err(|_| missing_ir())?;
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().map_or(false, |ext| ext == "ll")
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| name.starts_with(PROBE_CRATE))
+        })
+        .max_by_key(|path| {
+            path.metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .ok_or_else(missing_ir)
+}
+
+/// Parses the sizes and alignments of `type_names` from the LLVM IR of the probe crate.
+fn parse_layouts(ir: &str, type_names: &[String]) -> Result<Vec<(usize, usize)>, LayoutProbeError> {
+    let mut sizes = BTreeMap::new();
+    let mut aligns = BTreeMap::new();
+    for line in ir.lines() {
+        let (lengths, symbol) = if let Some(symbol) = line.strip_prefix('@') {
+            if let Some(index) = symbol.strip_prefix(SIZE_PREFIX) {
+                (&mut sizes, index)
+            } else if let Some(index) = symbol.strip_prefix(ALIGN_PREFIX) {
+                (&mut aligns, index)
+            } else {
+                continue;
+            }
+        } else {
+            continue;
+        };
+        let parse_error = || LayoutProbeError::Parse {
+            line: line.to_owned(),
+        };
+        let (index, definition) = symbol.split_once(" = ").ok_or_else(parse_error)?;
+        let index = index.parse::<usize>().map_err(|_| parse_error())?;
+        lengths.insert(index, parse_length(definition).ok_or_else(parse_error)?);
+    }
+
+    type_names
+        .iter()
+        .enumerate()
+        .map(
+            |(index, type_name)| match (sizes.get(&index), aligns.get(&index)) {
+                (Some(size), Some(align)) => Ok((*size, *align)),
+                _ => Err(LayoutProbeError::MissingLayout {
+                    type_name: type_name.clone(),
+                }),
+            },
+        )
+        .collect()
+}
+
+/// Parses the length of a byte array definition, e.g. `constant [24 x i8] zeroinitializer`.
+/// Empty arrays are emitted as `constant <{}> zeroinitializer`.
+fn parse_length(definition: &str) -> Option<usize> {
+    if let Some((_, array)) = definition.split_once('[') {
+        let (length, _) = array.split_once(" x i8]")?;
+        length.parse().ok()
+    } else if definition.contains("<{}>") {
+        Some(0)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_layouts() {
+        let type_names = ["String".to_owned(), "()".to_owned(), "[u32; 4]".to_owned()];
+        let ir = r#"
+@TRUC_ALIGN_0 = constant [8 x i8] zeroinitializer, align 1
+@TRUC_SIZE_0 = constant [24 x i8] zeroinitializer, align 1
+@TRUC_ALIGN_1 = dso_local constant [1 x i8] zeroinitializer, align 1
+@TRUC_SIZE_1 = constant <{}> zeroinitializer, align 1
+@TRUC_ALIGN_2 = internal global <{ [4 x i8] }> zeroinitializer, align 1
+@TRUC_SIZE_2 = local_unnamed_addr constant [16 x i8] zeroinitializer, align 1
+@other = constant [3 x i8] zeroinitializer, align 1
+"#;
+        assert_eq!(
+            parse_layouts(ir, &type_names).unwrap(),
+            vec![(24, 8), (0, 1), (16, 4)]
+        );
+
+        let error =
+            parse_layouts(&ir.replace("@TRUC_SIZE_1", "@TRUC_SIZE_2"), &type_names).unwrap_err();
+        let type_name =
+            assert_matches!(error, LayoutProbeError::MissingLayout { type_name } => type_name);
+        assert_eq!(type_name, "()");
+
+        let error = parse_layouts("@TRUC_SIZE_0 = constant i32 0", &type_names).unwrap_err();
+        assert_matches!(error, LayoutProbeError::Parse { .. });
+    }
+
+    #[test]
+    fn test_probe_source() {
+        assert_eq!(
+            probe_source(&["Box < str >".to_owned()]),
+            r#"// Generated by truc, do not edit.
+
+#[no_mangle]
+pub static TRUC_SIZE_0: [u8; core::mem::size_of::<Box < str >>()] = [0; core::mem::size_of::<Box < str >>()];
+
+#[no_mangle]
+pub static TRUC_ALIGN_0: [u8; core::mem::align_of::<Box < str >>()] = [0; core::mem::align_of::<Box < str >>()];
+"#
+        );
+    }
+
+    #[test]
+    fn test_host_layouts() {
+        let work_dir = env::temp_dir().join(format!("truc_layout_probe_{}", std::process::id()));
+
+        let mut host_resolver = StaticTypeResolver::new();
+        host_resolver.add_std_types();
+
+        let target_resolver = LayoutProbe::new(&work_dir)
+            .type_resolver(&host_resolver)
+            .unwrap();
+
+        for type_name in host_resolver.type_names() {
+            let host_info = host_resolver.dynamic_type_info(type_name);
+            let target_info = target_resolver.dynamic_type_info(type_name);
+            assert_eq!(target_info.info, host_info.info);
+            assert_eq!(target_info.allow_uninit, host_info.allow_uninit);
+        }
+        assert_eq!(target_resolver.header(), Some(&TypesHeader::current()));
+
+        std::fs::remove_dir_all(&work_dir).unwrap();
+    }
+
+    #[test]
+    fn test_user_type_layouts() {
+        let work_dir =
+            env::temp_dir().join(format!("truc_layout_probe_user_{}", std::process::id()));
+
+        // The layout of the user type does not depend on the target
+        let user_dir = work_dir.join("user_types");
+        create_dir_all(&user_dir.join("src")).unwrap();
+        write(
+            &user_dir.join("Cargo.toml"),
+            "[package]\nname = \"user_types\"\nversion = \"0.0.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        write(
+            &user_dir.join("src").join("lib.rs"),
+            "#[repr(C)]\npub struct Padded {\n    pub byte: u8,\n    pub integer: u32,\n}\n",
+        )
+        .unwrap();
+
+        let mut host_resolver = StaticTypeResolver::new();
+        host_resolver.add_dynamic_type(DynamicTypeInfo {
+            info: TypeInfo {
+                name: "user_types::Padded".to_owned(),
+                size: 0,
+                align: 1,
+                needs_drop: false,
+            },
+            allow_uninit: true,
+        });
+
+        let target_resolver = LayoutProbe::new(work_dir.join("probe"))
+            .with_dependency(format!("user_types = {{ path = {:?} }}", user_dir))
+            .type_resolver(&host_resolver)
+            .unwrap();
+
+        let info = target_resolver.dynamic_type_info("user_types::Padded");
+        assert_eq!((info.info.size, info.info.align), (8, 4));
+        assert!(!info.info.needs_drop);
+        assert!(info.allow_uninit);
+
+        std::fs::remove_dir_all(&work_dir).unwrap();
+    }
+}
@@ -0,0 +1,598 @@
+//! Build script helpers, especially for cross-compilation.
+//!
+//! When cross-compiling, type information cannot be computed by the build script since it runs on
+//! the host. It has to be computed beforehand on the target (see
+//! [target_types_json](crate::record::type_resolver::StaticTypeResolver::target_types_json)), or
+//! computed on the host by compiling a probe crate for the target (see [layout]), and written to a
+//! JSON file which is then loaded by the build script.
+//!
+//! Cross-compilation is requested by setting the `TRUC_CROSS` environment variable. The JSON file
+//! is then expected at `$CARGO_TARGET_DIR/<shared dir>/<target>/<profile>/target_types.json`,
+//! unless `TRUC_TARGET_TYPES` gives its path explicitly. `TRUC_TARGET_TYPES` can also list several
+//! files separated like in `PATH`, e.g. one per crate, which are then merged.
+//!
+//! Files are refused if their header tells they were computed for another target, pointer width
+//! or compiler. Files without header, written by former versions, cannot be checked.
+//!
+//! ```rust,no_run
+//! use truc::{build::BuildInfo, record::type_resolver::StaticTypeResolver};
+//!
+//! let build_info = BuildInfo::from_env("shared_truc").unwrap();
+//!
+//! let type_resolver = build_info
+//!     .type_resolver({
+//!         let mut resolver = StaticTypeResolver::new();
+//!         resolver.add_all_types();
+//!         resolver
+//!     })
+//!     .unwrap();
+//! ```
+
+pub mod layout;
+
+use std::{
+    collections::BTreeSet,
+    env,
+    ffi::{OsStr, OsString},
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use itertools::Itertools;
+
+use crate::record::type_resolver::{MergeError, StaticTypeResolver, TypesHeader};
+
+/// Environment variable requesting cross-compilation.
+pub const TRUC_CROSS: &str = "TRUC_CROSS";
+
+/// Environment variable overriding the paths of the target types JSON files.
+pub const TRUC_TARGET_TYPES: &str = "TRUC_TARGET_TYPES";
+
+/// Name of the target types JSON file in the shared directory.
+pub const TARGET_TYPES_FILE: &str = "target_types.json";
+
+/// Cross-compilation mode.
+#[derive(PartialEq, Eq, Debug)]
+pub enum CrossCompilation {
+    /// Types are resolved on the host which is also the target.
+    No,
+    /// Types are resolved by loading JSON files computed for the target.
+    Yes {
+        /// Paths of the target types JSON files.
+        target_types_paths: Vec<PathBuf>,
+    },
+}
+
+/// Errors of the build helpers.
+#[derive(Debug)]
+pub enum BuildError {
+    /// A required environment variable is not set.
+    MissingEnv(&'static str),
+    /// An environment variable has an invalid value.
+    InvalidEnv {
+        /// Name of the environment variable.
+        name: &'static str,
+        /// Value of the environment variable.
+        value: String,
+    },
+    /// The version of the Rust compiler could not be determined.
+    Rustc(std::io::Error),
+    /// The host is different from the target but `TRUC_CROSS` is not set.
+    CrossCompilationNotDetected {
+        /// Host triple.
+        host: String,
+        /// Target triple.
+        target: String,
+    },
+    /// The target types JSON file could not be read.
+    Read {
+        /// Path of the target types JSON file.
+        path: PathBuf,
+        /// Cause of the error.
+        error: std::io::Error,
+    },
+    /// The target types JSON file could not be parsed.
+    Parse {
+        /// Path of the target types JSON file.
+        path: PathBuf,
+        /// Cause of the error.
+        error: serde_json::Error,
+    },
+    /// The target types JSON file was computed for another target or compiler.
+    HeaderMismatch {
+        /// Path of the target types JSON file.
+        path: PathBuf,
+        /// Header of the current target and compiler.
+        expected: Box<TypesHeader>,
+        /// Header of the target types JSON file.
+        found: Box<TypesHeader>,
+    },
+    /// The target types JSON file conflicts with the previous ones.
+    Merge {
+        /// Path of the target types JSON file.
+        path: PathBuf,
+        /// Cause of the error.
+        error: Box<MergeError>,
+    },
+    /// The target types JSON files do not contain all the types known by the host, they are
+    /// probably stale.
+    MissingTypes {
+        /// Paths of the target types JSON files.
+        paths: Vec<PathBuf>,
+        /// Names of the missing types.
+        types: Vec<String>,
+    },
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingEnv(name) => write!(f, "Environment variable {} is not set", name),
+            Self::InvalidEnv { name, value } => {
+                write!(f, "Environment variable {} is invalid: {:?}", name, value)
+            }
+            Self::Rustc(error) => write!(f, "Could not get the rustc version: {}", error),
+            Self::CrossCompilationNotDetected { host, target } => write!(
+                f,
+                "Cross compilation not detected for target {} on host {}, set {} to enable it",
+                target, host, TRUC_CROSS
+            ),
+            Self::Read { path, error } => write!(
+                f,
+                "Could not read target types {:?}: {}, it needs to be generated on the target",
+                path, error
+            ),
+            Self::Parse { path, error } => {
+                write!(f, "Could not parse target types {:?}: {}", path, error)
+            }
+            Self::HeaderMismatch {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Target types {:?} are computed for {}, they need to be generated again for {}",
+                path, found, expected
+            ),
+            Self::Merge { path, error } => {
+                write!(f, "Could not merge target types {:?}: {}", path, error)
+            }
+            Self::MissingTypes { paths, types } => write!(
+                f,
+                "Target types {:?} are stale, they need to be generated again on the target, missing types: {}",
+                paths,
+                types.iter().join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Build information read from the environment of build scripts.
+#[derive(Debug)]
+pub struct BuildInfo {
+    out_dir_path: PathBuf,
+    target: String,
+    pointer_width: Option<u32>,
+    rustc: OsString,
+    cross_compilation: CrossCompilation,
+}
+
+impl BuildInfo {
+    /// Reads the build information from the environment of the build script.
+    ///
+    /// `shared_dir` is the directory, relative to `CARGO_TARGET_DIR`, where target types are
+    /// shared with the target.
+    pub fn from_env(shared_dir: &str) -> Result<Self, BuildError> {
+        println!("cargo:rerun-if-env-changed={}", TRUC_CROSS);
+        println!("cargo:rerun-if-env-changed={}", TRUC_TARGET_TYPES);
+        Self::from_vars(shared_dir, |name| env::var(name).ok())
+    }
+
+    fn from_vars<V>(shared_dir: &str, var: V) -> Result<Self, BuildError>
+    where
+        V: Fn(&'static str) -> Option<String>,
+    {
+        let required = |name| var(name).ok_or(BuildError::MissingEnv(name));
+
+        let out_dir_path = PathBuf::from(required("OUT_DIR")?);
+        let host = required("HOST")?;
+        let target = required("TARGET")?;
+
+        let (cross_compilation, pointer_width) = if var(TRUC_CROSS).is_some() {
+            let target_types_paths = match var(TRUC_TARGET_TYPES) {
+                Some(paths) => env::split_paths(&paths).collect(),
+                None => vec![PathBuf::from(required("CARGO_TARGET_DIR")?)
+                    .join(shared_dir)
+                    .join(&target)
+                    .join(required("PROFILE")?)
+                    .join(TARGET_TYPES_FILE)],
+            };
+            let pointer_width = required("CARGO_CFG_TARGET_POINTER_WIDTH")?;
+            let pointer_width =
+                pointer_width
+                    .parse::<u32>()
+                    .map_err(|_| BuildError::InvalidEnv {
+                        name: "CARGO_CFG_TARGET_POINTER_WIDTH",
+                        value: pointer_width.clone(),
+                    })?;
+            (
+                CrossCompilation::Yes { target_types_paths },
+                Some(pointer_width),
+            )
+        } else if host != target {
+            return Err(BuildError::CrossCompilationNotDetected { host, target });
+        } else {
+            (CrossCompilation::No, None)
+        };
+
+        Ok(Self {
+            out_dir_path,
+            target,
+            pointer_width,
+            rustc: var("RUSTC").map_or_else(|| OsString::from("rustc"), OsString::from),
+            cross_compilation,
+        })
+    }
+
+    /// Gets the output directory of the build script.
+    pub fn out_dir_path(&self) -> &Path {
+        &self.out_dir_path
+    }
+
+    /// Gets the cross-compilation mode.
+    pub fn cross_compilation(&self) -> &CrossCompilation {
+        &self.cross_compilation
+    }
+
+    /// Gives the type resolver to use for the target.
+    ///
+    /// `host_resolver` is returned as is when not cross-compiling. Otherwise the target types are
+    /// loaded, merged, checked against the current target and compiler, and checked to contain at
+    /// least all the types of `host_resolver`.
+    pub fn type_resolver(
+        &self,
+        host_resolver: StaticTypeResolver,
+    ) -> Result<StaticTypeResolver, BuildError> {
+        match &self.cross_compilation {
+            CrossCompilation::No => Ok(host_resolver),
+            CrossCompilation::Yes { target_types_paths } => {
+                for path in target_types_paths {
+                    println!("cargo:rerun-if-changed={}", path.display());
+                }
+                let expected_header = TypesHeader {
+                    target: self.target.clone(),
+                    pointer_width: self.pointer_width.expect("pointer width"),
+                    rustc_version: rustc_info(&self.rustc).map_err(BuildError::Rustc)?.version,
+                };
+                load_target_types(target_types_paths, &host_resolver, &expected_header)
+            }
+        }
+    }
+}
+
+fn load_target_types(
+    paths: &[PathBuf],
+    host_resolver: &StaticTypeResolver,
+    expected_header: &TypesHeader,
+) -> Result<StaticTypeResolver, BuildError> {
+    let mut target_types = StaticTypeResolver::new();
+    for path in paths {
+        let json = std::fs::read_to_string(path).map_err(|error| BuildError::Read {
+            path: path.to_owned(),
+            error,
+        })?;
+        let types: StaticTypeResolver =
+            serde_json::from_str(&json).map_err(|error| BuildError::Parse {
+                path: path.to_owned(),
+                error,
+            })?;
+        match types.header() {
+            Some(header) if header != expected_header => {
+                return Err(BuildError::HeaderMismatch {
+                    path: path.to_owned(),
+                    expected: Box::new(expected_header.clone()),
+                    found: Box::new(header.clone()),
+                });
+            }
+            Some(_) => {}
+            None => println!(
+                "cargo:warning=Target types {:?} have no header, they cannot be checked against the target and the compiler",
+                path
+            ),
+        }
+        target_types
+            .merge(types)
+            .map_err(|error| BuildError::Merge {
+                path: path.to_owned(),
+                error: Box::new(error),
+            })?;
+    }
+    let target_type_names = target_types.type_names().collect::<BTreeSet<_>>();
+    let missing_types = host_resolver
+        .type_names()
+        .filter(|type_name| !target_type_names.contains(type_name))
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    if !missing_types.is_empty() {
+        return Err(BuildError::MissingTypes {
+            paths: paths.to_vec(),
+            types: missing_types,
+        });
+    }
+    Ok(target_types)
+}
+
+/// Information given by `rustc -vV`.
+pub(crate) struct RustcInfo {
+    /// Version, as given by `rustc -V`.
+    pub version: String,
+    /// Host triple.
+    pub host: String,
+}
+
+pub(crate) fn rustc_info(rustc: &OsStr) -> std::io::Result<RustcInfo> {
+    let output = Command::new(rustc).arg("-vV").output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let version = lines.next().unwrap_or_default().trim().to_owned();
+    let host = lines
+        .find_map(|line| line.strip_prefix("host:"))
+        .unwrap_or_default()
+        .trim()
+        .to_owned();
+    Ok(RustcInfo { version, host })
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use maplit::btreemap;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::record::type_resolver::TypeResolver;
+
+    fn vars(vars: BTreeMap<&'static str, &'static str>) -> impl Fn(&'static str) -> Option<String> {
+        move |name| vars.get(name).map(|value| (*value).to_owned())
+    }
+
+    #[test]
+    fn test_no_cross_compilation() {
+        let build_info = BuildInfo::from_vars(
+            "shared",
+            vars(btreemap! {
+                "OUT_DIR" => "out",
+                "HOST" => "x86_64-unknown-linux-gnu",
+                "TARGET" => "x86_64-unknown-linux-gnu",
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(build_info.out_dir_path(), Path::new("out"));
+        assert_eq!(build_info.cross_compilation(), &CrossCompilation::No);
+
+        let mut host_resolver = StaticTypeResolver::new();
+        host_resolver.add_std_types();
+        let type_resolver = build_info.type_resolver(host_resolver).unwrap();
+        assert_eq!(type_resolver.type_info::<u32>().info.size, 4);
+    }
+
+    #[test]
+    fn test_cross_compilation_not_detected() {
+        let error = BuildInfo::from_vars(
+            "shared",
+            vars(btreemap! {
+                "OUT_DIR" => "out",
+                "HOST" => "x86_64-unknown-linux-gnu",
+                "TARGET" => "aarch64-unknown-linux-gnu",
+            }),
+        )
+        .unwrap_err();
+
+        assert_matches!(error, BuildError::CrossCompilationNotDetected { .. });
+        assert_eq!(
+            error.to_string(),
+            "Cross compilation not detected for target aarch64-unknown-linux-gnu on host x86_64-unknown-linux-gnu, set TRUC_CROSS to enable it"
+        );
+    }
+
+    #[test]
+    fn test_missing_env() {
+        let error = BuildInfo::from_vars(
+            "shared",
+            vars(btreemap! {
+                "OUT_DIR" => "out",
+                "HOST" => "x86_64-unknown-linux-gnu",
+                "TARGET" => "aarch64-unknown-linux-gnu",
+                "TRUC_CROSS" => "1",
+            }),
+        )
+        .unwrap_err();
+
+        assert_matches!(error, BuildError::MissingEnv("CARGO_TARGET_DIR"));
+    }
+
+    #[test]
+    fn test_cross_compilation_path() {
+        let build_info = BuildInfo::from_vars(
+            "shared",
+            vars(btreemap! {
+                "OUT_DIR" => "out",
+                "HOST" => "x86_64-unknown-linux-gnu",
+                "TARGET" => "aarch64-unknown-linux-gnu",
+                "TRUC_CROSS" => "1",
+                "CARGO_TARGET_DIR" => "target",
+                "PROFILE" => "debug",
+                "CARGO_CFG_TARGET_POINTER_WIDTH" => "64",
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            build_info.cross_compilation(),
+            &CrossCompilation::Yes {
+                target_types_paths: vec![Path::new("target")
+                    .join("shared")
+                    .join("aarch64-unknown-linux-gnu")
+                    .join("debug")
+                    .join("target_types.json")]
+            }
+        );
+
+        let target_types = env::join_paths(["types.json", "other_types.json"])
+            .unwrap()
+            .into_string()
+            .unwrap();
+        let build_info = BuildInfo::from_vars("shared", |name| match name {
+            "OUT_DIR" => Some("out".to_owned()),
+            "HOST" => Some("x86_64-unknown-linux-gnu".to_owned()),
+            "TARGET" => Some("aarch64-unknown-linux-gnu".to_owned()),
+            "TRUC_CROSS" => Some("1".to_owned()),
+            "TRUC_TARGET_TYPES" => Some(target_types.clone()),
+            "CARGO_CFG_TARGET_POINTER_WIDTH" => Some("64".to_owned()),
+            _ => None,
+        })
+        .unwrap();
+
+        assert_eq!(
+            build_info.cross_compilation(),
+            &CrossCompilation::Yes {
+                target_types_paths: vec![
+                    PathBuf::from("types.json"),
+                    PathBuf::from("other_types.json")
+                ]
+            }
+        );
+
+        let error = BuildInfo::from_vars(
+            "shared",
+            vars(btreemap! {
+                "OUT_DIR" => "out",
+                "HOST" => "x86_64-unknown-linux-gnu",
+                "TARGET" => "aarch64-unknown-linux-gnu",
+                "TRUC_CROSS" => "1",
+                "TRUC_TARGET_TYPES" => "types.json",
+                "CARGO_CFG_TARGET_POINTER_WIDTH" => "wide",
+            }),
+        )
+        .unwrap_err();
+
+        assert_matches!(
+            error,
+            BuildError::InvalidEnv {
+                name: "CARGO_CFG_TARGET_POINTER_WIDTH",
+                ..
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_target_types() {
+        let dir = env::temp_dir().join(format!("truc_build_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(TARGET_TYPES_FILE);
+        let paths = [path.clone()];
+        let header = TypesHeader::current();
+
+        let host_resolver = {
+            let mut resolver = StaticTypeResolver::new();
+            resolver.add_type_allow_uninit::<u32>();
+            resolver.add_type::<String>();
+            resolver
+        };
+
+        let error = load_target_types(&paths, &host_resolver, &header).unwrap_err();
+        assert_matches!(error, BuildError::Read { .. });
+
+        std::fs::write(&path, "not json").unwrap();
+        let error = load_target_types(&paths, &host_resolver, &header).unwrap_err();
+        assert_matches!(error, BuildError::Parse { .. });
+
+        std::fs::write(&path, {
+            let mut resolver = StaticTypeResolver::new();
+            resolver.add_type_allow_uninit::<u32>();
+            resolver.to_json_string().unwrap()
+        })
+        .unwrap();
+        let error = load_target_types(&paths, &host_resolver, &header).unwrap_err();
+        let types = assert_matches!(&error, BuildError::MissingTypes { types, .. } => types);
+        assert_eq!(types, &["String".to_owned()]);
+        assert!(error.to_string().ends_with("missing types: String"));
+
+        std::fs::write(&path, host_resolver.to_json_string().unwrap()).unwrap();
+        let type_resolver = load_target_types(&paths, &host_resolver, &header).unwrap();
+        assert!(type_resolver.type_info::<u32>().allow_uninit);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_merged_target_types() {
+        let dir = env::temp_dir().join(format!("truc_build_merged_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = [dir.join("std_types.json"), dir.join("my_types.json")];
+        let header = TypesHeader {
+            target: "aarch64-unknown-linux-gnu".to_owned(),
+            pointer_width: 64,
+            rustc_version: "rustc 1.95.0".to_owned(),
+        };
+
+        let host_resolver = {
+            let mut resolver = StaticTypeResolver::new();
+            resolver.add_type_allow_uninit::<u32>();
+            resolver.add_type::<String>();
+            resolver
+        };
+
+        let write_types = |path: &Path, header: &TypesHeader, add: fn(&mut StaticTypeResolver)| {
+            let mut resolver = StaticTypeResolver::new();
+            resolver.set_header(header.clone());
+            add(&mut resolver);
+            std::fs::write(path, resolver.to_json_string().unwrap()).unwrap();
+        };
+
+        write_types(&paths[0], &header, |resolver| {
+            resolver.add_type_allow_uninit::<u32>()
+        });
+        write_types(&paths[1], &header, |resolver| {
+            resolver.add_type::<u32>();
+            resolver.add_type::<String>();
+        });
+        let error = load_target_types(&paths, &host_resolver, &header).unwrap_err();
+        let path = assert_matches!(error, BuildError::Merge { path, .. } => path);
+        assert_eq!(path, paths[1]);
+
+        write_types(&paths[1], &header, |resolver| resolver.add_type::<String>());
+        let type_resolver = load_target_types(&paths, &host_resolver, &header).unwrap();
+        assert_eq!(type_resolver.header(), Some(&header));
+        assert!(type_resolver.type_info::<u32>().allow_uninit);
+        assert!(!type_resolver.type_info::<String>().allow_uninit);
+
+        let other_header = TypesHeader {
+            rustc_version: "rustc 1.56.1".to_owned(),
+            ..header.clone()
+        };
+        write_types(&paths[1], &other_header, |resolver| {
+            resolver.add_type::<String>()
+        });
+        let error = load_target_types(&paths, &host_resolver, &header).unwrap_err();
+        let found = assert_matches!(&error, BuildError::HeaderMismatch { found, .. } => found);
+        assert_eq!(**found, other_header);
+        assert!(error.to_string().contains(
+            "are computed for target aarch64-unknown-linux-gnu (64 bits) with rustc 1.56.1"
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -18,6 +18,7 @@ use super::fragment::{
 /// Main configuration entry point.
 pub struct GeneratorConfig {
     pub(crate) fragment_generators: Vec<Box<dyn FragmentGenerator>>,
+    pub(crate) no_std: bool,
 }
 
 impl GeneratorConfig {
@@ -28,9 +29,20 @@ impl GeneratorConfig {
     pub fn new(fragment_generators: impl IntoIterator<Item = Box<dyn FragmentGenerator>>) -> Self {
         Self {
             fragment_generators: fragment_generators.into_iter().collect(),
+            no_std: false,
         }
     }
 
+    /// Makes the generated code refer to `core::` paths instead of `std::` paths so that it can be
+    /// included in `no_std` crates.
+    ///
+    /// The generated code never allocates by itself, `alloc::` paths are only needed by datum
+    /// types and are up to the type names of the definition.
+    pub fn with_no_std(mut self) -> Self {
+        self.no_std = true;
+        self
+    }
+
     /// Extends the fragment generators with the ones passed as argument.
     pub fn with_fragment_generators(
         mut self,
@@ -85,6 +97,7 @@ impl Default for GeneratorConfig {
     fn default() -> Self {
         Self {
             fragment_generators: Vec::new(),
+            no_std: false,
         }
         .with_common_fragments()
     }
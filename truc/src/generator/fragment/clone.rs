@@ -67,6 +67,11 @@ impl FragmentGenerator for CloneImplGenerator {
     fn generate(&self, specs: &FragmentGeneratorSpecs, scope: &mut Scope) {
         let record_spec = specs.record;
 
+        // `Copy` records already derive `Clone`
+        if record_spec.is_copy() {
+            return;
+        }
+
         Self::generate_clone_impl(record_spec, scope);
     }
 }
@@ -81,7 +86,11 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::{config::GeneratorConfig, generate_variant, tests::assert_fragment_eq},
+        generator::{
+            config::GeneratorConfig,
+            generate_variant,
+            tests::{add_not_copy_integer, assert_fragment_eq},
+        },
         record::{
             definition::builder::native::NativeRecordDefinitionBuilder,
             type_resolver::HostTypeResolver,
@@ -98,7 +107,7 @@ mod tests {
             GeneratorConfig::new([Box::new(CloneImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -107,32 +116,20 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
-        assert_fragment_eq(
-            r#"
-impl<const CAP: usize> Clone for CappedRecord0<CAP> {
-    fn clone(&self) -> Self {
-        Self::from(UnpackedRecord0 {
-        })
-    }
-
-    fn clone_from(&mut self, _source: &Self) {
-    }
-}
-"#,
-            &scope.to_string(),
-        );
+        // Empty records are `Copy`
+        assert_fragment_eq("", &scope.to_string());
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 
     #[test]
     fn should_generate_clone_impl_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.add_datum_allow_uninit::<u32, _>("integer").unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -140,7 +137,7 @@ impl<const CAP: usize> Clone for CappedRecord0<CAP> {
             GeneratorConfig::new([Box::new(CloneImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -149,7 +146,7 @@ impl<const CAP: usize> Clone for CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -172,8 +169,12 @@ impl<const CAP: usize> Clone for CappedRecord0<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
@@ -183,7 +184,7 @@ impl<const CAP: usize> Clone for CappedRecord0<CAP> {
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -193,7 +194,7 @@ impl<const CAP: usize> Clone for CappedRecord0<CAP> {
         builder
             .add_datum_allow_uninit::<u32, _>("integer1")
             .unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer1").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer1");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -201,7 +202,7 @@ impl<const CAP: usize> Clone for CappedRecord0<CAP> {
             GeneratorConfig::new([Box::new(CloneImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -210,10 +211,10 @@ impl<const CAP: usize> Clone for CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -221,7 +222,7 @@ impl<const CAP: usize> Clone for CappedRecord0<CAP> {
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -246,8 +247,12 @@ impl<const CAP: usize> Clone for CappedRecord1<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 }
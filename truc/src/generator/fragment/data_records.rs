@@ -50,7 +50,13 @@ using it."#,
                 },
             ),
         ] {
-            generate_data_record(record_info, &record_spec.data, uninit_kind, scope);
+            generate_data_record(
+                record_info,
+                &record_spec.data,
+                uninit_kind,
+                specs.std,
+                scope,
+            );
         }
     }
 }
@@ -65,7 +71,11 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::{config::GeneratorConfig, generate_variant, tests::assert_fragment_eq},
+        generator::{
+            config::GeneratorConfig,
+            generate_variant,
+            tests::{add_not_copy_integer, assert_fragment_eq},
+        },
         record::{
             definition::builder::native::NativeRecordDefinitionBuilder,
             type_resolver::HostTypeResolver,
@@ -82,7 +92,7 @@ mod tests {
             GeneratorConfig::new([Box::new(DataRecordsGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -91,7 +101,7 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -120,14 +130,14 @@ impl From<UnpackedUninitRecord0> for UnpackedUninitSafeRecord0 {
             &scope.to_string(),
         );
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 
     #[test]
     fn should_generate_data_record_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.add_datum_allow_uninit::<u32, _>("integer").unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -135,7 +145,7 @@ impl From<UnpackedUninitRecord0> for UnpackedUninitSafeRecord0 {
             GeneratorConfig::new([Box::new(DataRecordsGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -144,7 +154,7 @@ impl From<UnpackedUninitRecord0> for UnpackedUninitSafeRecord0 {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -182,8 +192,12 @@ impl<T0: Copy> From<UnpackedUninitRecord0> for UnpackedUninitSafeRecord0<T0> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
@@ -193,7 +207,7 @@ impl<T0: Copy> From<UnpackedUninitRecord0> for UnpackedUninitSafeRecord0<T0> {
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -203,7 +217,7 @@ impl<T0: Copy> From<UnpackedUninitRecord0> for UnpackedUninitSafeRecord0<T0> {
         builder
             .add_datum_allow_uninit::<u32, _>("integer1")
             .unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer1").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer1");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -211,7 +225,7 @@ impl<T0: Copy> From<UnpackedUninitRecord0> for UnpackedUninitSafeRecord0<T0> {
             GeneratorConfig::new([Box::new(DataRecordsGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -220,10 +234,10 @@ impl<T0: Copy> From<UnpackedUninitRecord0> for UnpackedUninitSafeRecord0<T0> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -231,7 +245,7 @@ impl<T0: Copy> From<UnpackedUninitRecord0> for UnpackedUninitSafeRecord0<T0> {
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -271,8 +285,12 @@ impl<T0: Copy, T1: Copy> From<UnpackedUninitRecord1> for UnpackedUninitSafeRecor
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 }
@@ -9,6 +9,11 @@ impl FragmentGenerator for DropImplGenerator {
     fn generate(&self, specs: &FragmentGeneratorSpecs, scope: &mut Scope) {
         let record_spec = &specs.record;
 
+        // No need to implement Drop when all datums are trivially dropped
+        if !record_spec.needs_drop() {
+            return;
+        }
+
         let drop_impl = scope
             .new_impl(&record_spec.capped_record_name)
             .generic(CAP_GENERIC)
@@ -17,7 +22,11 @@ impl FragmentGenerator for DropImplGenerator {
 
         let drop_fn = drop_impl.new_fn("drop").arg_mut_self();
 
-        for datum in &record_spec.data {
+        for datum in record_spec
+            .data
+            .iter()
+            .filter(|datum| datum.details().needs_drop())
+        {
             drop_fn.line(format!(
                 "let _{}: {} = unsafe {{ self.data.read({}) }};",
                 datum.name(),
@@ -38,7 +47,11 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::{config::GeneratorConfig, generate_variant, tests::assert_fragment_eq},
+        generator::{
+            config::GeneratorConfig,
+            generate_variant,
+            tests::{add_not_copy_integer, assert_fragment_eq},
+        },
         record::{
             definition::builder::native::NativeRecordDefinitionBuilder,
             type_resolver::HostTypeResolver,
@@ -55,7 +68,7 @@ mod tests {
             GeneratorConfig::new([Box::new(DropImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -64,27 +77,19 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
-        assert_fragment_eq(
-            r#"
-impl<const CAP: usize> Drop for CappedRecord0<CAP> {
-    fn drop(&mut self) {
-    }
-}
-"#,
-            &scope.to_string(),
-        );
+        assert_fragment_eq("", &scope.to_string());
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 
     #[test]
     fn should_generate_drop_impl_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.add_datum_allow_uninit::<u32, _>("integer").unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -92,7 +97,7 @@ impl<const CAP: usize> Drop for CappedRecord0<CAP> {
             GeneratorConfig::new([Box::new(DropImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -101,14 +106,13 @@ impl<const CAP: usize> Drop for CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> Drop for CappedRecord0<CAP> {
     fn drop(&mut self) {
-        let _integer: u32 = unsafe { self.data.read(0) };
         let _not_copy_integer: u32 = unsafe { self.data.read(4) };
     }
 }
@@ -117,8 +121,12 @@ impl<const CAP: usize> Drop for CappedRecord0<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
@@ -128,7 +136,7 @@ impl<const CAP: usize> Drop for CappedRecord0<CAP> {
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -138,7 +146,7 @@ impl<const CAP: usize> Drop for CappedRecord0<CAP> {
         builder
             .add_datum_allow_uninit::<u32, _>("integer1")
             .unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer1").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer1");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -146,7 +154,7 @@ impl<const CAP: usize> Drop for CappedRecord0<CAP> {
             GeneratorConfig::new([Box::new(DropImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -155,10 +163,10 @@ impl<const CAP: usize> Drop for CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -166,15 +174,13 @@ impl<const CAP: usize> Drop for CappedRecord0<CAP> {
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> Drop for CappedRecord1<CAP> {
     fn drop(&mut self) {
-        let _boolean1: bool = unsafe { self.data.read(8) };
-        let _integer1: u32 = unsafe { self.data.read(0) };
         let _not_copy_integer1: u32 = unsafe { self.data.read(4) };
     }
 }
@@ -183,8 +189,12 @@ impl<const CAP: usize> Drop for CappedRecord1<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 }
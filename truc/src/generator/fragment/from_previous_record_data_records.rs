@@ -51,7 +51,13 @@ impl FragmentGenerator for FromPreviousRecordDataRecordsGenerator {
                 },
             ),
         ] {
-            generate_data_record(record_info, &record_spec.plus_data, uninit_kind, scope);
+            generate_data_record(
+                record_info,
+                &record_spec.plus_data,
+                uninit_kind,
+                specs.std,
+                scope,
+            );
         }
 
         generate_data_out_record(
@@ -83,7 +89,11 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::{config::GeneratorConfig, generate_variant, tests::assert_fragment_eq},
+        generator::{
+            config::GeneratorConfig,
+            generate_variant,
+            tests::{add_not_copy_integer, assert_fragment_eq},
+        },
         record::{
             definition::builder::native::NativeRecordDefinitionBuilder,
             type_resolver::HostTypeResolver,
@@ -101,7 +111,7 @@ mod tests {
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -110,7 +120,7 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -119,14 +129,14 @@ mod tests {
             &scope.to_string(),
         );
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 
     #[test]
     fn should_generate_data_records_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.add_datum_allow_uninit::<u32, _>("integer").unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -135,7 +145,7 @@ mod tests {
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -144,7 +154,7 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -154,8 +164,12 @@ mod tests {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
@@ -165,7 +179,7 @@ mod tests {
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -175,7 +189,7 @@ mod tests {
         builder
             .add_datum_allow_uninit::<u32, _>("integer1")
             .unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer1").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer1");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -184,7 +198,7 @@ mod tests {
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -193,10 +207,10 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -204,7 +218,7 @@ mod tests {
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -245,8 +259,12 @@ pub struct Record1AndUnpackedOut<const CAP: usize> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 }
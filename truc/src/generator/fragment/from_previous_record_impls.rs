@@ -23,6 +23,7 @@ impl FromPreviousRecordImplsGenerator {
         prev_record_spec: &RecordSpec,
         from_kind: FromKind,
         into_kind: IntoKind,
+        std: &str,
         scope: &mut Scope,
     ) {
         let from_type = format!(
@@ -64,6 +65,10 @@ impl FromPreviousRecordImplsGenerator {
                 from_type,
             )
             .ret("Self");
+        from_fn.line(format!(
+            "let () = {}::<{}>::CAP_CHECK;",
+            record_spec.capped_record_name, CAP
+        ));
 
         if from_kind == FromKind::FromUninit {
             from_fn.line(format!(
@@ -81,7 +86,12 @@ impl FromPreviousRecordImplsGenerator {
             ));
         }
 
-        for datum in &record_spec.minus_data {
+        // Removed datums are read in order to be dropped, unless they are trivially dropped
+        for datum in record_spec
+            .minus_data
+            .iter()
+            .filter(|datum| into_kind == IntoKind::IntoAndOut || datum.details().needs_drop())
+        {
             from_fn.line(format!(
                 "let {}{}: {} = unsafe {{ from.data.read({}) }};",
                 match into_kind {
@@ -94,38 +104,56 @@ impl FromPreviousRecordImplsGenerator {
             ));
         }
 
-        from_fn.line("let manually_drop = std::mem::ManuallyDrop::new(from);");
         from_fn.line(format!(
-            "let {}data = unsafe {{ std::ptr::read(&manually_drop.data) }};",
-            if from_kind == FromKind::FromFull && plus_has_data
-                || from_kind == FromKind::FromUninit && uninit_plus_has_data
-            {
-                "mut "
-            } else {
-                ""
-            }
+            "let manually_drop = {}::mem::ManuallyDrop::new(from);",
+            std
         ));
-
-        for datum in record_spec
-            .plus_data
-            .iter()
-            .filter(|datum| from_kind == FromKind::FromFull || !datum.details().allow_uninit())
-        {
+        let has_writes = from_kind == FromKind::FromFull && plus_has_data
+            || from_kind == FromKind::FromUninit && uninit_plus_has_data;
+        let record = format!(
+            "{} {{ data: unsafe {{ {std}::ptr::read(&manually_drop.data) }}, _datums: {std}::marker::PhantomData }}",
+            match into_kind {
+                IntoKind::IntoSimple => "Self",
+                IntoKind::IntoAndOut => &record_spec.capped_record_name,
+            },
+            std = std,
+        );
+        if into_kind == IntoKind::IntoSimple && !has_writes {
+            from_fn.line(record);
+            return;
+        }
+        if has_writes {
+            // Data is written in the aligned record, never in an unaligned local copy, which is not
+            // dropped before all the data is written
             from_fn.line(format!(
-                "unsafe {{ data.write({}, plus.{}); }}",
-                datum.details().offset(),
-                datum.name(),
+                "let mut record = {}::mem::ManuallyDrop::new({});",
+                std, record
             ));
+            for datum in record_spec
+                .plus_data
+                .iter()
+                .filter(|datum| from_kind == FromKind::FromFull || !datum.details().allow_uninit())
+            {
+                from_fn.line(format!(
+                    "unsafe {{ record.data.write({}, plus.{}); }}",
+                    datum.details().offset(),
+                    datum.name(),
+                ));
+            }
+        } else {
+            from_fn.line(format!("let record = {};", record));
         }
         match into_kind {
             IntoKind::IntoSimple => {
-                from_fn.line("Self { data }");
+                from_fn.line(format!("{}::mem::ManuallyDrop::into_inner(record)", std));
             }
             IntoKind::IntoAndOut => {
-                from_fn.line(format!(
-                    "let record = {} {{ data }};",
-                    record_spec.capped_record_name
-                ));
+                if has_writes {
+                    from_fn.line(format!(
+                        "let record = {}::mem::ManuallyDrop::into_inner(record);",
+                        std
+                    ));
+                }
                 from_fn.line(format!(
                     "{} {{ record{} }}",
                     record_spec.record_and_unpacked_out_name,
@@ -158,6 +186,7 @@ impl FragmentGenerator for FromPreviousRecordImplsGenerator {
                 prev_record_spec,
                 from_kind,
                 into_kind,
+                specs.std,
                 scope,
             );
         }
@@ -174,7 +203,11 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::{config::GeneratorConfig, generate_variant, tests::assert_fragment_eq},
+        generator::{
+            config::GeneratorConfig,
+            generate_variant,
+            tests::{add_not_copy_integer, assert_fragment_eq},
+        },
         record::{
             definition::builder::native::NativeRecordDefinitionBuilder,
             type_resolver::HostTypeResolver,
@@ -192,7 +225,7 @@ mod tests {
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -201,7 +234,7 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -210,14 +243,14 @@ mod tests {
             &scope.to_string(),
         );
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 
     #[test]
     fn should_generate_impls_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.add_datum_allow_uninit::<u32, _>("integer").unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -226,7 +259,7 @@ mod tests {
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -235,7 +268,7 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -245,8 +278,12 @@ mod tests {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
@@ -256,7 +293,7 @@ mod tests {
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -266,7 +303,7 @@ mod tests {
         builder
             .add_datum_allow_uninit::<u32, _>("integer1")
             .unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer1").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer1");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -275,7 +312,7 @@ mod tests {
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -284,10 +321,10 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -295,57 +332,59 @@ mod tests {
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedRecordIn1)> for CappedRecord1<CAP> {
     fn from((from, plus): (CappedRecord0<CAP>, UnpackedRecordIn1)) -> Self {
-        let _integer0: u32 = unsafe { from.data.read(0) };
+        let () = CappedRecord1::<CAP>::CAP_CHECK;
         let _not_copy_integer0: u32 = unsafe { from.data.read(4) };
         let manually_drop = std::mem::ManuallyDrop::new(from);
-        let mut data = unsafe { std::ptr::read(&manually_drop.data) };
-        unsafe { data.write(0, plus.integer1); }
-        unsafe { data.write(4, plus.not_copy_integer1); }
-        Self { data }
+        let mut record = std::mem::ManuallyDrop::new(Self { data: unsafe { std::ptr::read(&manually_drop.data) }, _datums: std::marker::PhantomData });
+        unsafe { record.data.write(0, plus.integer1); }
+        unsafe { record.data.write(4, plus.not_copy_integer1); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 }
 
 impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for CappedRecord1<CAP> {
     fn from((from, plus): (CappedRecord0<CAP>, UnpackedUninitRecordIn1)) -> Self {
+        let () = CappedRecord1::<CAP>::CAP_CHECK;
         let plus = UnpackedUninitSafeRecordIn1::<u32>::from(plus);
-        let _integer0: u32 = unsafe { from.data.read(0) };
         let _not_copy_integer0: u32 = unsafe { from.data.read(4) };
         let manually_drop = std::mem::ManuallyDrop::new(from);
-        let mut data = unsafe { std::ptr::read(&manually_drop.data) };
-        unsafe { data.write(4, plus.not_copy_integer1); }
-        Self { data }
+        let mut record = std::mem::ManuallyDrop::new(Self { data: unsafe { std::ptr::read(&manually_drop.data) }, _datums: std::marker::PhantomData });
+        unsafe { record.data.write(4, plus.not_copy_integer1); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 }
 
 impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedRecordIn1)> for Record1AndUnpackedOut<CAP> {
     fn from((from, plus): (CappedRecord0<CAP>, UnpackedRecordIn1)) -> Self {
+        let () = CappedRecord1::<CAP>::CAP_CHECK;
         let integer0: u32 = unsafe { from.data.read(0) };
         let not_copy_integer0: u32 = unsafe { from.data.read(4) };
         let manually_drop = std::mem::ManuallyDrop::new(from);
-        let mut data = unsafe { std::ptr::read(&manually_drop.data) };
-        unsafe { data.write(0, plus.integer1); }
-        unsafe { data.write(4, plus.not_copy_integer1); }
-        let record = CappedRecord1 { data };
+        let mut record = std::mem::ManuallyDrop::new(CappedRecord1 { data: unsafe { std::ptr::read(&manually_drop.data) }, _datums: std::marker::PhantomData });
+        unsafe { record.data.write(0, plus.integer1); }
+        unsafe { record.data.write(4, plus.not_copy_integer1); }
+        let record = std::mem::ManuallyDrop::into_inner(record);
         Record1AndUnpackedOut { record, integer0, not_copy_integer0 }
     }
 }
 
 impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for Record1AndUnpackedOut<CAP> {
     fn from((from, plus): (CappedRecord0<CAP>, UnpackedUninitRecordIn1)) -> Self {
+        let () = CappedRecord1::<CAP>::CAP_CHECK;
         let plus = UnpackedUninitSafeRecordIn1::<u32>::from(plus);
         let integer0: u32 = unsafe { from.data.read(0) };
         let not_copy_integer0: u32 = unsafe { from.data.read(4) };
         let manually_drop = std::mem::ManuallyDrop::new(from);
-        let mut data = unsafe { std::ptr::read(&manually_drop.data) };
-        unsafe { data.write(4, plus.not_copy_integer1); }
-        let record = CappedRecord1 { data };
+        let mut record = std::mem::ManuallyDrop::new(CappedRecord1 { data: unsafe { std::ptr::read(&manually_drop.data) }, _datums: std::marker::PhantomData });
+        unsafe { record.data.write(4, plus.not_copy_integer1); }
+        let record = std::mem::ManuallyDrop::into_inner(record);
         Record1AndUnpackedOut { record, integer0, not_copy_integer0 }
     }
 }
@@ -354,8 +393,12 @@ impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for R
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
@@ -365,7 +408,7 @@ impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for R
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -380,7 +423,7 @@ impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for R
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -389,10 +432,10 @@ impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for R
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -400,51 +443,49 @@ impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for R
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedRecordIn1)> for CappedRecord1<CAP> {
     fn from((from, _plus): (CappedRecord0<CAP>, UnpackedRecordIn1)) -> Self {
-        let _integer0: u32 = unsafe { from.data.read(0) };
+        let () = CappedRecord1::<CAP>::CAP_CHECK;
         let _not_copy_integer0: u32 = unsafe { from.data.read(4) };
         let manually_drop = std::mem::ManuallyDrop::new(from);
-        let data = unsafe { std::ptr::read(&manually_drop.data) };
-        Self { data }
+        Self { data: unsafe { std::ptr::read(&manually_drop.data) }, _datums: std::marker::PhantomData }
     }
 }
 
 impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for CappedRecord1<CAP> {
     fn from((from, plus): (CappedRecord0<CAP>, UnpackedUninitRecordIn1)) -> Self {
+        let () = CappedRecord1::<CAP>::CAP_CHECK;
         let _plus = UnpackedUninitSafeRecordIn1::from(plus);
-        let _integer0: u32 = unsafe { from.data.read(0) };
         let _not_copy_integer0: u32 = unsafe { from.data.read(4) };
         let manually_drop = std::mem::ManuallyDrop::new(from);
-        let data = unsafe { std::ptr::read(&manually_drop.data) };
-        Self { data }
+        Self { data: unsafe { std::ptr::read(&manually_drop.data) }, _datums: std::marker::PhantomData }
     }
 }
 
 impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedRecordIn1)> for Record1AndUnpackedOut<CAP> {
     fn from((from, _plus): (CappedRecord0<CAP>, UnpackedRecordIn1)) -> Self {
+        let () = CappedRecord1::<CAP>::CAP_CHECK;
         let integer0: u32 = unsafe { from.data.read(0) };
         let not_copy_integer0: u32 = unsafe { from.data.read(4) };
         let manually_drop = std::mem::ManuallyDrop::new(from);
-        let data = unsafe { std::ptr::read(&manually_drop.data) };
-        let record = CappedRecord1 { data };
+        let record = CappedRecord1 { data: unsafe { std::ptr::read(&manually_drop.data) }, _datums: std::marker::PhantomData };
         Record1AndUnpackedOut { record, integer0, not_copy_integer0 }
     }
 }
 
 impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for Record1AndUnpackedOut<CAP> {
     fn from((from, plus): (CappedRecord0<CAP>, UnpackedUninitRecordIn1)) -> Self {
+        let () = CappedRecord1::<CAP>::CAP_CHECK;
         let _plus = UnpackedUninitSafeRecordIn1::from(plus);
         let integer0: u32 = unsafe { from.data.read(0) };
         let not_copy_integer0: u32 = unsafe { from.data.read(4) };
         let manually_drop = std::mem::ManuallyDrop::new(from);
-        let data = unsafe { std::ptr::read(&manually_drop.data) };
-        let record = CappedRecord1 { data };
+        let record = CappedRecord1 { data: unsafe { std::ptr::read(&manually_drop.data) }, _datums: std::marker::PhantomData };
         Record1AndUnpackedOut { record, integer0, not_copy_integer0 }
     }
 }
@@ -452,6 +493,6 @@ impl<const CAP: usize> From<(CappedRecord0<CAP>, UnpackedUninitRecordIn1)> for R
             &scope.to_string(),
         );
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 }
@@ -30,6 +30,8 @@ pub trait FragmentGenerator {
 pub struct FragmentGeneratorSpecs<'a> {
     pub record: &'a RecordSpec<'a>,
     pub prev_record: Option<&'a RecordSpec<'a>>,
+    /// Crate to refer to for `mem`, `marker`, `ptr` and `fmt` items, either `std` or `core`.
+    pub std: &'static str,
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -52,6 +54,28 @@ pub struct RecordSpec<'a> {
     pub plus_uninit_safe_generic: Option<RecordGeneric>,
 }
 
+impl RecordSpec<'_> {
+    /// Gets the size of the record variant, that is the end offset of its last datum.
+    pub fn variant_size(&self) -> usize {
+        self.data
+            .iter()
+            .map(|d| d.details().offset() + d.details().size())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Indicates whether or not some datums of the record variant need to be dropped.
+    pub fn needs_drop(&self) -> bool {
+        self.data.iter().any(|d| d.details().needs_drop())
+    }
+
+    /// Indicates whether or not the record variant can be `Copy`, that is all its datums are
+    /// `Copy` and none of them needs to be dropped.
+    pub fn is_copy(&self) -> bool {
+        !self.needs_drop() && self.data.iter().all(|d| d.details().allow_uninit())
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct RecordGeneric {
     pub full: String,
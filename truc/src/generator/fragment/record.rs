@@ -1,7 +1,11 @@
-use codegen::{Scope, Type};
+use codegen::{Field, Scope, Type};
+use itertools::Itertools;
 
 use super::{FragmentGenerator, FragmentGeneratorSpecs};
-use crate::generator::{CAP, CAP_GENERIC};
+use crate::{
+    generator::{const_assertion, CAP, CAP_GENERIC},
+    record::definition::DatumId,
+};
 
 pub struct RecordGenerator;
 
@@ -18,10 +22,29 @@ impl FragmentGenerator for RecordGenerator {
             .repr(&format!("align({})", record_spec.max_type_align))
             .vis("pub")
             .generic(CAP_GENERIC);
+        // There is no Drop implementation to prevent it
+        if record_spec.is_copy() {
+            record.derive("Clone").derive("Copy");
+        }
 
         let mut uninit_type = Type::new("RecordMaybeUninit");
         uninit_type.generic(CAP);
         record.field("data", &uninit_type);
+        // Makes the record `Send`/`Sync` only if all its datums are
+        let mut datums_field = Field::new(
+            "_datums",
+            format!(
+                "{}::marker::PhantomData<({})>",
+                specs.std,
+                record_spec
+                    .data
+                    .iter()
+                    .map(|datum| format!("{},", datum.details().type_name()))
+                    .join(" ")
+            ),
+        );
+        datums_field.annotation("#[allow(clippy::type_complexity)]");
+        record.push_field(datums_field);
 
         if let Some(prev_record_spec) = specs.prev_record {
             record.doc(&format!(
@@ -42,6 +65,69 @@ It may be created from initial data via one of [`new`](Self::new) or [`new_unini
                     ));
         }
 
+        let const_names = datum_const_names(
+            record_spec
+                .data
+                .iter()
+                .map(|datum| (datum.id(), datum.name())),
+        );
+
+        let variant_size = record_spec.variant_size();
+        // Any capacity fits an empty variant
+        let cap_check = if variant_size > 0 {
+            const_assertion(
+                &format!("{} >= Self::VARIANT_SIZE", CAP),
+                &format!(
+                    "CAP of {} is smaller than its variant size",
+                    record_spec.capped_record_name
+                ),
+            )
+        } else {
+            "()".to_owned()
+        };
+        scope.raw(format!(
+            r#"impl<{}> {}<{}> {{
+    /// Size of the record variant, that is the end offset of its last datum.
+    ///
+    /// It may be used as a tighter `CAP` const generic when the record is never converted into
+    /// a bigger variant.
+    pub const VARIANT_SIZE: usize = {};
+{}
+    /// Fails the compilation when `CAP` is smaller than [`VARIANT_SIZE`](Self::VARIANT_SIZE).
+    const CAP_CHECK: () = {};
+}}"#,
+            CAP_GENERIC,
+            record_spec.capped_record_name,
+            CAP,
+            variant_size,
+            record_spec
+                .data
+                .iter()
+                .zip(&const_names)
+                .filter_map(|(datum, const_name)| {
+                    const_name.as_ref().map(|const_name| (datum, const_name))
+                })
+                .map(|(datum, const_name)| {
+                    format!(
+                        r#"
+    /// Offset of `{name}` in the record.
+    pub const OFFSET_OF_{const_name}: usize = {offset};
+    /// Size of `{name}`.
+    pub const SIZE_OF_{const_name}: usize = {size};
+    /// Alignment of `{name}`.
+    pub const ALIGN_OF_{const_name}: usize = {align};
+"#,
+                        name = datum.name(),
+                        const_name = const_name,
+                        offset = datum.details().offset(),
+                        size = datum.details().size(),
+                        align = datum.details().type_align(),
+                    )
+                })
+                .join(""),
+            cap_check,
+        ));
+
         scope.raw(format!(
             r#"/// Record variant #{} with optimized capacity.
 pub type {} = {}<{{ MAX_SIZE }}>;"#,
@@ -49,9 +135,97 @@ pub type {} = {}<{{ MAX_SIZE }}>;"#,
             record_spec.record_name,
             record_spec.capped_record_name,
         ));
+
+        scope.raw(format!(
+            "const _: () = {};",
+            const_assertion(
+                &format!(
+                    "{}::mem::align_of::<{}>() == {}",
+                    specs.std, record_spec.record_name, record_spec.max_type_align
+                ),
+                &format!(
+                    "Alignment of {} is not {}",
+                    record_spec.record_name, record_spec.max_type_align
+                ),
+            )
+        ));
+
+        // This checks every datum is placed according to the alignment of its actual type
+        for (datum, const_name) in record_spec.data.iter().zip(&const_names) {
+            let offset = if let Some(const_name) = const_name {
+                format!("{}::OFFSET_OF_{}", record_spec.record_name, const_name)
+            } else {
+                datum.details().offset().to_string()
+            };
+            scope.raw(format!(
+                "const _: () = {};",
+                const_assertion(
+                    &format!(
+                        "{} % {}::mem::align_of::<{}>() == 0",
+                        offset,
+                        specs.std,
+                        datum.details().type_name(),
+                    ),
+                    &format!(
+                        "Offset of {} in {} is misaligned",
+                        datum.name(),
+                        record_spec.record_name
+                    ),
+                )
+            ));
+        }
+
+        // The resolver may be wrong about a datum being `Copy`, the compiler is not
+        if record_spec.is_copy() && !record_spec.data.is_empty() {
+            scope.raw(format!(
+                r#"const _: fn() = || {{
+    fn assert_copy<T: Copy>() {{}}
+    assert_copy::<({})>();
+}};"#,
+                record_spec
+                    .data
+                    .iter()
+                    .map(|datum| format!("{},", datum.details().type_name()))
+                    .join(" ")
+            ));
+        }
     }
 }
 
+/// Gives the suffix of the layout constants of each datum, its upper-case name with the raw
+/// identifier marker stripped, e.g. `OFFSET_OF_{NAME}`.
+///
+/// Datums whose upper-case names collide get their datum identifier appended, e.g.
+/// `OFFSET_OF_{NAME}_{ID}`. In the unlikely case the result still collides with another datum, the
+/// datum gets no constant at all.
+fn datum_const_names<'a>(
+    data: impl Iterator<Item = (DatumId, &'a str)> + Clone,
+) -> Vec<Option<String>> {
+    let upper_case = |name: &str| name.trim_start_matches("r#").to_uppercase();
+    let counts = data.clone().map(|(_, name)| upper_case(name)).counts();
+    let names = data
+        .map(|(id, name)| {
+            let name = upper_case(name);
+            if counts[&name] == 1 {
+                name
+            } else {
+                format!("{}_{}", name, id)
+            }
+        })
+        .collect::<Vec<_>>();
+    let counts = names.iter().counts();
+    names
+        .iter()
+        .map(|name| {
+            if counts[name] == 1 {
+                Some(name.clone())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
@@ -62,7 +236,11 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::{config::GeneratorConfig, generate_variant, tests::assert_fragment_eq},
+        generator::{
+            config::GeneratorConfig,
+            generate_variant,
+            tests::{add_not_copy_integer, assert_fragment_eq},
+        },
         record::{
             definition::builder::native::NativeRecordDefinitionBuilder,
             type_resolver::HostTypeResolver,
@@ -70,6 +248,7 @@ mod tests {
     };
 
     #[test]
+    #[cfg_attr(feature = "msrv", ignore)]
     fn should_generate_empty_record() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.close_record_variant();
@@ -79,7 +258,7 @@ mod tests {
             GeneratorConfig::new([Box::new(RecordGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -88,7 +267,7 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -98,25 +277,42 @@ use truc_runtime::data::RecordMaybeUninit;
 /// Record variant #0.
 ///
 /// It may be created from initial data via one of [`new`](Self::new) or [`new_uninit`](Self::new_uninit)
+#[derive(Clone, Copy)]
 #[repr(align(1))]
 pub struct CappedRecord0<const CAP: usize> {
     data: RecordMaybeUninit<CAP>,
+    #[allow(clippy::type_complexity)]
+    _datums: std::marker::PhantomData<()>,
+}
+
+impl<const CAP: usize> CappedRecord0<CAP> {
+    /// Size of the record variant, that is the end offset of its last datum.
+    ///
+    /// It may be used as a tighter `CAP` const generic when the record is never converted into
+    /// a bigger variant.
+    pub const VARIANT_SIZE: usize = 0;
+
+    /// Fails the compilation when `CAP` is smaller than [`VARIANT_SIZE`](Self::VARIANT_SIZE).
+    const CAP_CHECK: () = ();
 }
 
 /// Record variant #0 with optimized capacity.
 pub type Record0 = CappedRecord0<{ MAX_SIZE }>;
+
+const _: () = assert!(std::mem::align_of::<Record0>() == 1, "Alignment of Record0 is not 1");
 "#,
             &scope.to_string(),
         );
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 
     #[test]
+    #[cfg_attr(feature = "msrv", ignore)]
     fn should_generate_record_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.add_datum_allow_uninit::<u32, _>("integer").unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -124,7 +320,7 @@ pub type Record0 = CappedRecord0<{ MAX_SIZE }>;
             GeneratorConfig::new([Box::new(RecordGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -133,7 +329,7 @@ pub type Record0 = CappedRecord0<{ MAX_SIZE }>;
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -146,27 +342,164 @@ use truc_runtime::data::RecordMaybeUninit;
 #[repr(align(4))]
 pub struct CappedRecord0<const CAP: usize> {
     data: RecordMaybeUninit<CAP>,
+    #[allow(clippy::type_complexity)]
+    _datums: std::marker::PhantomData<(u32, u32,)>,
+}
+
+impl<const CAP: usize> CappedRecord0<CAP> {
+    /// Size of the record variant, that is the end offset of its last datum.
+    ///
+    /// It may be used as a tighter `CAP` const generic when the record is never converted into
+    /// a bigger variant.
+    pub const VARIANT_SIZE: usize = 8;
+
+    /// Offset of `integer` in the record.
+    pub const OFFSET_OF_INTEGER: usize = 0;
+    /// Size of `integer`.
+    pub const SIZE_OF_INTEGER: usize = 4;
+    /// Alignment of `integer`.
+    pub const ALIGN_OF_INTEGER: usize = 4;
+
+    /// Offset of `not_copy_integer` in the record.
+    pub const OFFSET_OF_NOT_COPY_INTEGER: usize = 4;
+    /// Size of `not_copy_integer`.
+    pub const SIZE_OF_NOT_COPY_INTEGER: usize = 4;
+    /// Alignment of `not_copy_integer`.
+    pub const ALIGN_OF_NOT_COPY_INTEGER: usize = 4;
+
+    /// Fails the compilation when `CAP` is smaller than [`VARIANT_SIZE`](Self::VARIANT_SIZE).
+    const CAP_CHECK: () = assert!(CAP >= Self::VARIANT_SIZE, "CAP of CappedRecord0 is smaller than its variant size");
 }
 
 /// Record variant #0 with optimized capacity.
 pub type Record0 = CappedRecord0<{ MAX_SIZE }>;
+
+const _: () = assert!(std::mem::align_of::<Record0>() == 4, "Alignment of Record0 is not 4");
+
+const _: () = assert!(Record0::OFFSET_OF_INTEGER % std::mem::align_of::<u32>() == 0, "Offset of integer in Record0 is misaligned");
+
+const _: () = assert!(Record0::OFFSET_OF_NOT_COPY_INTEGER % std::mem::align_of::<u32>() == 0, "Offset of not_copy_integer in Record0 is misaligned");
 "#,
             &scope.to_string(),
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
     #[test]
+    #[cfg_attr(feature = "msrv", ignore)]
+    fn should_namespace_datum_consts() {
+        let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
+        builder.add_datum_allow_uninit::<u32, _>("variant").unwrap();
+        builder.add_datum_allow_uninit::<u8, _>("r#type").unwrap();
+        builder.add_datum_allow_uninit::<u8, _>("foo").unwrap();
+        builder.add_datum_allow_uninit::<u8, _>("Foo").unwrap();
+        builder.add_datum_allow_uninit::<u8, _>("foo_2").unwrap();
+        builder.close_record_variant();
+        let definition = builder.build();
+
+        let config =
+            GeneratorConfig::new([Box::new(RecordGenerator) as Box<dyn FragmentGenerator>]);
+
+        let mut scope = Scope::new();
+        let mut type_layout_assertions = BTreeSet::new();
+
+        generate_variant(
+            &definition,
+            definition.max_type_align(),
+            definition.variants().next().expect("variant"),
+            None,
+            &config,
+            &mut scope,
+            &mut type_layout_assertions,
+        );
+
+        assert_fragment_eq(
+            r#"
+use truc_runtime::data::RecordMaybeUninit;
+
+/// Record variant #0.
+///
+/// It may be created from initial data via one of [`new`](Self::new) or [`new_uninit`](Self::new_uninit)
+#[derive(Clone, Copy)]
+#[repr(align(4))]
+pub struct CappedRecord0<const CAP: usize> {
+    data: RecordMaybeUninit<CAP>,
+    #[allow(clippy::type_complexity)]
+    _datums: std::marker::PhantomData<(u32, u8, u8, u8, u8,)>,
+}
+
+impl<const CAP: usize> CappedRecord0<CAP> {
+    /// Size of the record variant, that is the end offset of its last datum.
+    ///
+    /// It may be used as a tighter `CAP` const generic when the record is never converted into
+    /// a bigger variant.
+    pub const VARIANT_SIZE: usize = 8;
+
+    /// Offset of `variant` in the record.
+    pub const OFFSET_OF_VARIANT: usize = 0;
+    /// Size of `variant`.
+    pub const SIZE_OF_VARIANT: usize = 4;
+    /// Alignment of `variant`.
+    pub const ALIGN_OF_VARIANT: usize = 4;
+
+    /// Offset of `r#type` in the record.
+    pub const OFFSET_OF_TYPE: usize = 4;
+    /// Size of `r#type`.
+    pub const SIZE_OF_TYPE: usize = 1;
+    /// Alignment of `r#type`.
+    pub const ALIGN_OF_TYPE: usize = 1;
+
+    /// Offset of `Foo` in the record.
+    pub const OFFSET_OF_FOO_3: usize = 6;
+    /// Size of `Foo`.
+    pub const SIZE_OF_FOO_3: usize = 1;
+    /// Alignment of `Foo`.
+    pub const ALIGN_OF_FOO_3: usize = 1;
+
+    /// Fails the compilation when `CAP` is smaller than [`VARIANT_SIZE`](Self::VARIANT_SIZE).
+    const CAP_CHECK: () = assert!(CAP >= Self::VARIANT_SIZE, "CAP of CappedRecord0 is smaller than its variant size");
+}
+
+/// Record variant #0 with optimized capacity.
+pub type Record0 = CappedRecord0<{ MAX_SIZE }>;
+
+const _: () = assert!(std::mem::align_of::<Record0>() == 4, "Alignment of Record0 is not 4");
+
+const _: () = assert!(Record0::OFFSET_OF_VARIANT % std::mem::align_of::<u32>() == 0, "Offset of variant in Record0 is misaligned");
+
+const _: () = assert!(Record0::OFFSET_OF_TYPE % std::mem::align_of::<u8>() == 0, "Offset of r#type in Record0 is misaligned");
+
+const _: () = assert!(5 % std::mem::align_of::<u8>() == 0, "Offset of foo in Record0 is misaligned");
+
+const _: () = assert!(Record0::OFFSET_OF_FOO_3 % std::mem::align_of::<u8>() == 0, "Offset of Foo in Record0 is misaligned");
+
+const _: () = assert!(7 % std::mem::align_of::<u8>() == 0, "Offset of foo_2 in Record0 is misaligned");
+
+const _: fn() = || {
+    fn assert_copy<T: Copy>() {}
+    assert_copy::<(u32, u8, u8, u8, u8,)>();
+};
+"#,
+            &scope.to_string(),
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "msrv", ignore)]
     fn should_generate_next_record_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -176,7 +509,7 @@ pub type Record0 = CappedRecord0<{ MAX_SIZE }>;
         builder
             .add_datum_allow_uninit::<u32, _>("integer1")
             .unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer1").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer1");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -184,7 +517,7 @@ pub type Record0 = CappedRecord0<{ MAX_SIZE }>;
             GeneratorConfig::new([Box::new(RecordGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -193,10 +526,10 @@ pub type Record0 = CappedRecord0<{ MAX_SIZE }>;
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -204,7 +537,7 @@ pub type Record0 = CappedRecord0<{ MAX_SIZE }>;
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -219,17 +552,63 @@ use truc_runtime::data::RecordMaybeUninit;
 #[repr(align(4))]
 pub struct CappedRecord1<const CAP: usize> {
     data: RecordMaybeUninit<CAP>,
+    #[allow(clippy::type_complexity)]
+    _datums: std::marker::PhantomData<(bool, u32, u32,)>,
+}
+
+impl<const CAP: usize> CappedRecord1<CAP> {
+    /// Size of the record variant, that is the end offset of its last datum.
+    ///
+    /// It may be used as a tighter `CAP` const generic when the record is never converted into
+    /// a bigger variant.
+    pub const VARIANT_SIZE: usize = 9;
+
+    /// Offset of `boolean1` in the record.
+    pub const OFFSET_OF_BOOLEAN1: usize = 8;
+    /// Size of `boolean1`.
+    pub const SIZE_OF_BOOLEAN1: usize = 1;
+    /// Alignment of `boolean1`.
+    pub const ALIGN_OF_BOOLEAN1: usize = 1;
+
+    /// Offset of `integer1` in the record.
+    pub const OFFSET_OF_INTEGER1: usize = 0;
+    /// Size of `integer1`.
+    pub const SIZE_OF_INTEGER1: usize = 4;
+    /// Alignment of `integer1`.
+    pub const ALIGN_OF_INTEGER1: usize = 4;
+
+    /// Offset of `not_copy_integer1` in the record.
+    pub const OFFSET_OF_NOT_COPY_INTEGER1: usize = 4;
+    /// Size of `not_copy_integer1`.
+    pub const SIZE_OF_NOT_COPY_INTEGER1: usize = 4;
+    /// Alignment of `not_copy_integer1`.
+    pub const ALIGN_OF_NOT_COPY_INTEGER1: usize = 4;
+
+    /// Fails the compilation when `CAP` is smaller than [`VARIANT_SIZE`](Self::VARIANT_SIZE).
+    const CAP_CHECK: () = assert!(CAP >= Self::VARIANT_SIZE, "CAP of CappedRecord1 is smaller than its variant size");
 }
 
 /// Record variant #1 with optimized capacity.
 pub type Record1 = CappedRecord1<{ MAX_SIZE }>;
+
+const _: () = assert!(std::mem::align_of::<Record1>() == 4, "Alignment of Record1 is not 4");
+
+const _: () = assert!(Record1::OFFSET_OF_BOOLEAN1 % std::mem::align_of::<bool>() == 0, "Offset of boolean1 in Record1 is misaligned");
+
+const _: () = assert!(Record1::OFFSET_OF_INTEGER1 % std::mem::align_of::<u32>() == 0, "Offset of integer1 in Record1 is misaligned");
+
+const _: () = assert!(Record1::OFFSET_OF_NOT_COPY_INTEGER1 % std::mem::align_of::<u32>() == 0, "Offset of not_copy_integer1 in Record1 is misaligned");
 "#,
             &scope.to_string(),
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 }
@@ -21,6 +21,7 @@ impl RecordImplGenerator {
     fn generate_constructor(
         record_spec: &RecordSpec,
         uninit_kind: UninitKind,
+        std: &str,
         record_impl: &mut Impl,
     ) {
         let has_data = !record_spec.data.is_empty();
@@ -41,6 +42,7 @@ impl RecordImplGenerator {
                 },
             )
             .ret("Self");
+        new_fn.line("let () = Self::CAP_CHECK;");
         let uninit_has_data = if let UninitKind::Uninit { safe_record_name } = uninit_kind {
             let uninit_has_data = record_spec
                 .data
@@ -59,28 +61,40 @@ impl RecordImplGenerator {
         } else {
             false
         };
+        let record = format!(
+            "Self {{ data: RecordMaybeUninit::new(), _datums: {}::marker::PhantomData }}",
+            std
+        );
+        if !matches!(
+            (&uninit_kind, has_data, uninit_has_data),
+            (UninitKind::Full, true, _) | (UninitKind::Uninit { .. }, _, true)
+        ) {
+            new_fn.line(record);
+            return;
+        }
+        // Data is written in the aligned record, never in an unaligned local copy, which is not
+        // dropped before all the data is written
         new_fn.line(format!(
-            "let {}data = RecordMaybeUninit::new();",
-            match (&uninit_kind, has_data, uninit_has_data) {
-                (UninitKind::Full, true, _) | (UninitKind::Uninit { .. }, _, true) => "mut ",
-                _ => "",
-            },
+            "let mut record = {}::mem::ManuallyDrop::new({});",
+            std, record
         ));
         for datum in record_spec.data.iter().filter(|datum| {
             matches!(uninit_kind, UninitKind::Full) || !datum.details().allow_uninit()
         }) {
             new_fn.line(format!(
-                "unsafe {{ data.write({}, from.{}); }}",
+                "unsafe {{ record.data.write({}, from.{}); }}",
                 datum.details().offset(),
                 datum.name()
             ));
         }
-        new_fn.line("Self { data }");
+        new_fn.line(format!("{}::mem::ManuallyDrop::into_inner(record)", std));
     }
 
     fn generate_unpacker(
         data: &[&DatumDefinition<NativeDatumDetails>],
         unpacked_record_name: &str,
+        needs_drop: bool,
+        std: &str,
         record_impl: &mut Impl,
     ) {
         let unpack_fn = record_impl
@@ -96,7 +110,9 @@ impl RecordImplGenerator {
                 datum.details().offset(),
             ));
         }
-        unpack_fn.line("std::mem::forget(self);");
+        if needs_drop {
+            unpack_fn.line(format!("{}::mem::forget(self);", std));
+        }
         unpack_fn.line(format!(
             "{} {{ {} }}",
             unpacked_record_name,
@@ -106,6 +122,33 @@ impl RecordImplGenerator {
                 .join(", ")
         ));
     }
+
+    fn generate_cap_resizer(capped_record_name: &str, std: &str, record_impl: &mut Impl) {
+        let resize_fn = record_impl
+            .new_fn("resize_cap")
+            .generic("const NEW_CAP: usize")
+            .arg_self()
+            .vis("pub")
+            .ret(format!("{}<NEW_CAP>", capped_record_name));
+        resize_fn.line(format!(
+            "let () = {}::<NEW_CAP>::CAP_CHECK;",
+            capped_record_name
+        ));
+        resize_fn.line(format!(
+            "let manually_drop = {}::mem::ManuallyDrop::new(self);",
+            std
+        ));
+        // Data is copied in the aligned record, never in an unaligned local copy, which is not
+        // dropped before all the data is copied
+        resize_fn.line(format!(
+            "let mut record = {std}::mem::ManuallyDrop::new({} {{ data: RecordMaybeUninit::new(), _datums: {std}::marker::PhantomData }});",
+            capped_record_name,
+            std = std
+        ));
+        resize_fn
+            .line("unsafe { record.data.copy_from(&manually_drop.data, Self::VARIANT_SIZE); }");
+        resize_fn.line(format!("{}::mem::ManuallyDrop::into_inner(record)", std));
+    }
 }
 
 impl FragmentGenerator for RecordImplGenerator {
@@ -117,22 +160,27 @@ impl FragmentGenerator for RecordImplGenerator {
             .generic(CAP_GENERIC)
             .target_generic(CAP);
 
-        Self::generate_constructor(record_spec, UninitKind::Full, record_impl);
+        Self::generate_constructor(record_spec, UninitKind::Full, specs.std, record_impl);
 
         Self::generate_constructor(
             record_spec,
             UninitKind::Uninit {
                 safe_record_name: &record_spec.unpacked_uninit_safe_record_name,
             },
+            specs.std,
             record_impl,
         );
 
         Self::generate_unpacker(
             &record_spec.data,
             &record_spec.unpacked_record_name,
+            record_spec.needs_drop(),
+            specs.std,
             record_impl,
         );
 
+        Self::generate_cap_resizer(&record_spec.capped_record_name, specs.std, record_impl);
+
         for datum in &record_spec.data {
             record_impl
                 .new_fn(datum.name())
@@ -169,7 +217,11 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::{config::GeneratorConfig, generate_variant, tests::assert_fragment_eq},
+        generator::{
+            config::GeneratorConfig,
+            generate_variant,
+            tests::{add_not_copy_integer, assert_fragment_eq},
+        },
         record::{
             definition::builder::native::NativeRecordDefinitionBuilder,
             type_resolver::HostTypeResolver,
@@ -186,7 +238,7 @@ mod tests {
             GeneratorConfig::new([Box::new(RecordImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -195,40 +247,47 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> CappedRecord0<CAP> {
     pub fn new(_from: UnpackedRecord0) -> Self {
-        let data = RecordMaybeUninit::new();
-        Self { data }
+        let () = Self::CAP_CHECK;
+        Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData }
     }
 
     pub fn new_uninit(from: UnpackedUninitRecord0) -> Self {
+        let () = Self::CAP_CHECK;
         let _from = UnpackedUninitSafeRecord0::from(from);
-        let data = RecordMaybeUninit::new();
-        Self { data }
+        Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData }
     }
 
     pub fn unpack(self) -> UnpackedRecord0 {
-        std::mem::forget(self);
         UnpackedRecord0 {  }
     }
+
+    pub fn resize_cap<const NEW_CAP: usize>(self) -> CappedRecord0<NEW_CAP> {
+        let () = CappedRecord0::<NEW_CAP>::CAP_CHECK;
+        let manually_drop = std::mem::ManuallyDrop::new(self);
+        let mut record = std::mem::ManuallyDrop::new(CappedRecord0 { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.copy_from(&manually_drop.data, Self::VARIANT_SIZE); }
+        std::mem::ManuallyDrop::into_inner(record)
+    }
 }
 "#,
             &scope.to_string(),
         );
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 
     #[test]
     fn should_generate_record_impl_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.add_datum_allow_uninit::<u32, _>("integer").unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -236,7 +295,7 @@ impl<const CAP: usize> CappedRecord0<CAP> {
             GeneratorConfig::new([Box::new(RecordImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -245,24 +304,26 @@ impl<const CAP: usize> CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> CappedRecord0<CAP> {
     pub fn new(from: UnpackedRecord0) -> Self {
-        let mut data = RecordMaybeUninit::new();
-        unsafe { data.write(0, from.integer); }
-        unsafe { data.write(4, from.not_copy_integer); }
-        Self { data }
+        let () = Self::CAP_CHECK;
+        let mut record = std::mem::ManuallyDrop::new(Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.write(0, from.integer); }
+        unsafe { record.data.write(4, from.not_copy_integer); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 
     pub fn new_uninit(from: UnpackedUninitRecord0) -> Self {
+        let () = Self::CAP_CHECK;
         let from = UnpackedUninitSafeRecord0::<u32>::from(from);
-        let mut data = RecordMaybeUninit::new();
-        unsafe { data.write(4, from.not_copy_integer); }
-        Self { data }
+        let mut record = std::mem::ManuallyDrop::new(Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.write(4, from.not_copy_integer); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 
     pub fn unpack(self) -> UnpackedRecord0 {
@@ -272,6 +333,14 @@ impl<const CAP: usize> CappedRecord0<CAP> {
         UnpackedRecord0 { integer, not_copy_integer }
     }
 
+    pub fn resize_cap<const NEW_CAP: usize>(self) -> CappedRecord0<NEW_CAP> {
+        let () = CappedRecord0::<NEW_CAP>::CAP_CHECK;
+        let manually_drop = std::mem::ManuallyDrop::new(self);
+        let mut record = std::mem::ManuallyDrop::new(CappedRecord0 { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.copy_from(&manually_drop.data, Self::VARIANT_SIZE); }
+        std::mem::ManuallyDrop::into_inner(record)
+    }
+
     pub fn integer(&self) -> &u32 {
         unsafe { self.data.get::<u32>(0) }
     }
@@ -293,8 +362,12 @@ impl<const CAP: usize> CappedRecord0<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
@@ -304,7 +377,7 @@ impl<const CAP: usize> CappedRecord0<CAP> {
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -314,7 +387,7 @@ impl<const CAP: usize> CappedRecord0<CAP> {
         builder
             .add_datum_allow_uninit::<u32, _>("integer1")
             .unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer1").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer1");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -322,7 +395,7 @@ impl<const CAP: usize> CappedRecord0<CAP> {
             GeneratorConfig::new([Box::new(RecordImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -331,10 +404,10 @@ impl<const CAP: usize> CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -342,25 +415,27 @@ impl<const CAP: usize> CappedRecord0<CAP> {
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> CappedRecord1<CAP> {
     pub fn new(from: UnpackedRecord1) -> Self {
-        let mut data = RecordMaybeUninit::new();
-        unsafe { data.write(8, from.boolean1); }
-        unsafe { data.write(0, from.integer1); }
-        unsafe { data.write(4, from.not_copy_integer1); }
-        Self { data }
+        let () = Self::CAP_CHECK;
+        let mut record = std::mem::ManuallyDrop::new(Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.write(8, from.boolean1); }
+        unsafe { record.data.write(0, from.integer1); }
+        unsafe { record.data.write(4, from.not_copy_integer1); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 
     pub fn new_uninit(from: UnpackedUninitRecord1) -> Self {
+        let () = Self::CAP_CHECK;
         let from = UnpackedUninitSafeRecord1::<bool, u32>::from(from);
-        let mut data = RecordMaybeUninit::new();
-        unsafe { data.write(4, from.not_copy_integer1); }
-        Self { data }
+        let mut record = std::mem::ManuallyDrop::new(Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.write(4, from.not_copy_integer1); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 
     pub fn unpack(self) -> UnpackedRecord1 {
@@ -371,6 +446,14 @@ impl<const CAP: usize> CappedRecord1<CAP> {
         UnpackedRecord1 { boolean1, integer1, not_copy_integer1 }
     }
 
+    pub fn resize_cap<const NEW_CAP: usize>(self) -> CappedRecord1<NEW_CAP> {
+        let () = CappedRecord1::<NEW_CAP>::CAP_CHECK;
+        let manually_drop = std::mem::ManuallyDrop::new(self);
+        let mut record = std::mem::ManuallyDrop::new(CappedRecord1 { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.copy_from(&manually_drop.data, Self::VARIANT_SIZE); }
+        std::mem::ManuallyDrop::into_inner(record)
+    }
+
     pub fn boolean1(&self) -> &bool {
         unsafe { self.data.get::<bool>(8) }
     }
@@ -400,8 +483,12 @@ impl<const CAP: usize> CappedRecord1<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 }
@@ -17,6 +17,7 @@ impl RecordUnnamedImplGenerator {
     fn generate_constructor(
         record_spec: &RecordSpec,
         uninit_kind: UninitKind,
+        std: &str,
         record_impl: &mut Impl,
     ) {
         let has_data = !record_spec.data.is_empty();
@@ -37,6 +38,7 @@ impl RecordUnnamedImplGenerator {
                 })
             })
             .ret("Self");
+        new_fn.line("let () = Self::CAP_CHECK;");
         let uninit_has_data = match uninit_kind {
             UninitKind::Full => {
                 new_fn.line(format!(
@@ -74,23 +76,33 @@ impl RecordUnnamedImplGenerator {
                 uninit_has_data
             }
         };
+        let record = format!(
+            "Self {{ data: RecordMaybeUninit::new(), _datums: {}::marker::PhantomData }}",
+            std
+        );
+        if !matches!(
+            (&uninit_kind, has_data, uninit_has_data),
+            (UninitKind::Full, true, _) | (UninitKind::Uninit { .. }, _, true)
+        ) {
+            new_fn.line(record);
+            return;
+        }
+        // Data is written in the aligned record, never in an unaligned local copy, which is not
+        // dropped before all the data is written
         new_fn.line(format!(
-            "let {}data = RecordMaybeUninit::new();",
-            match (&uninit_kind, has_data, uninit_has_data) {
-                (UninitKind::Full, true, _) | (UninitKind::Uninit { .. }, _, true) => "mut ",
-                _ => "",
-            },
+            "let mut record = {}::mem::ManuallyDrop::new({});",
+            std, record
         ));
         for datum in record_spec.data.iter().filter(|datum| {
             matches!(uninit_kind, UninitKind::Full) || !datum.details().allow_uninit()
         }) {
             new_fn.line(format!(
-                "unsafe {{ data.write({}, from.{}); }}",
+                "unsafe {{ record.data.write({}, from.{}); }}",
                 datum.details().offset(),
                 datum.name()
             ));
         }
-        new_fn.line("Self { data }");
+        new_fn.line(format!("{}::mem::ManuallyDrop::into_inner(record)", std));
     }
 }
 
@@ -103,13 +115,14 @@ impl FragmentGenerator for RecordUnnamedImplGenerator {
             .generic(CAP_GENERIC)
             .target_generic(CAP);
 
-        Self::generate_constructor(record_spec, UninitKind::Full, record_impl);
+        Self::generate_constructor(record_spec, UninitKind::Full, specs.std, record_impl);
 
         Self::generate_constructor(
             record_spec,
             UninitKind::Uninit {
                 safe_record_name: &record_spec.unpacked_uninit_safe_record_name,
             },
+            specs.std,
             record_impl,
         );
     }
@@ -125,7 +138,11 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::{config::GeneratorConfig, generate_variant, tests::assert_fragment_eq},
+        generator::{
+            config::GeneratorConfig,
+            generate_variant,
+            tests::{add_not_copy_integer, assert_fragment_eq},
+        },
         record::{
             definition::builder::native::NativeRecordDefinitionBuilder,
             type_resolver::HostTypeResolver,
@@ -143,7 +160,7 @@ mod tests {
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -152,36 +169,36 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> CappedRecord0<CAP> {
     pub fn new_unnamed() -> Self {
+        let () = Self::CAP_CHECK;
         let _from = UnpackedRecord0 {};
-        let data = RecordMaybeUninit::new();
-        Self { data }
+        Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData }
     }
 
     pub fn new_uninit_unnamed() -> Self {
+        let () = Self::CAP_CHECK;
         let _from = UnpackedUninitSafeRecord0::from(UnpackedUninitRecord0 {});
-        let data = RecordMaybeUninit::new();
-        Self { data }
+        Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData }
     }
 }
 "#,
             &scope.to_string(),
         );
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 
     #[test]
     fn should_generate_record_impl_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.add_datum_allow_uninit::<u32, _>("integer").unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -190,7 +207,7 @@ impl<const CAP: usize> CappedRecord0<CAP> {
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -199,25 +216,27 @@ impl<const CAP: usize> CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> CappedRecord0<CAP> {
     pub fn new_unnamed(integer: u32, not_copy_integer: u32) -> Self {
+        let () = Self::CAP_CHECK;
         let from = UnpackedRecord0 { integer, not_copy_integer };
-        let mut data = RecordMaybeUninit::new();
-        unsafe { data.write(0, from.integer); }
-        unsafe { data.write(4, from.not_copy_integer); }
-        Self { data }
+        let mut record = std::mem::ManuallyDrop::new(Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.write(0, from.integer); }
+        unsafe { record.data.write(4, from.not_copy_integer); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 
     pub fn new_uninit_unnamed(not_copy_integer: u32) -> Self {
+        let () = Self::CAP_CHECK;
         let from = UnpackedUninitSafeRecord0::<u32>::from(UnpackedUninitRecord0 { not_copy_integer });
-        let mut data = RecordMaybeUninit::new();
-        unsafe { data.write(4, from.not_copy_integer); }
-        Self { data }
+        let mut record = std::mem::ManuallyDrop::new(Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.write(4, from.not_copy_integer); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 }
 "#,
@@ -225,8 +244,12 @@ impl<const CAP: usize> CappedRecord0<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
@@ -236,7 +259,7 @@ impl<const CAP: usize> CappedRecord0<CAP> {
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -246,7 +269,7 @@ impl<const CAP: usize> CappedRecord0<CAP> {
         builder
             .add_datum_allow_uninit::<u32, _>("integer1")
             .unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer1").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer1");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -255,7 +278,7 @@ impl<const CAP: usize> CappedRecord0<CAP> {
         ]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -264,10 +287,10 @@ impl<const CAP: usize> CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -275,26 +298,28 @@ impl<const CAP: usize> CappedRecord0<CAP> {
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
             r#"
 impl<const CAP: usize> CappedRecord1<CAP> {
     pub fn new_unnamed(boolean1: bool, integer1: u32, not_copy_integer1: u32) -> Self {
+        let () = Self::CAP_CHECK;
         let from = UnpackedRecord1 { boolean1, integer1, not_copy_integer1 };
-        let mut data = RecordMaybeUninit::new();
-        unsafe { data.write(8, from.boolean1); }
-        unsafe { data.write(0, from.integer1); }
-        unsafe { data.write(4, from.not_copy_integer1); }
-        Self { data }
+        let mut record = std::mem::ManuallyDrop::new(Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.write(8, from.boolean1); }
+        unsafe { record.data.write(0, from.integer1); }
+        unsafe { record.data.write(4, from.not_copy_integer1); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 
     pub fn new_uninit_unnamed(not_copy_integer1: u32) -> Self {
+        let () = Self::CAP_CHECK;
         let from = UnpackedUninitSafeRecord1::<bool, u32>::from(UnpackedUninitRecord1 { not_copy_integer1 });
-        let mut data = RecordMaybeUninit::new();
-        unsafe { data.write(4, from.not_copy_integer1); }
-        Self { data }
+        let mut record = std::mem::ManuallyDrop::new(Self { data: RecordMaybeUninit::new(), _datums: std::marker::PhantomData });
+        unsafe { record.data.write(4, from.not_copy_integer1); }
+        std::mem::ManuallyDrop::into_inner(record)
     }
 }
 "#,
@@ -302,8 +327,12 @@ impl<const CAP: usize> CappedRecord1<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 }
@@ -43,7 +43,7 @@ impl SerdeImplGenerator {
         serialize_fn.line("tuple.end()");
     }
 
-    fn generate_visitor(record_spec: &RecordSpec, deserialize_fn: &mut Function) {
+    fn generate_visitor(record_spec: &RecordSpec, std: &str, deserialize_fn: &mut Function) {
         let mut sub_scope = Scope::new();
 
         sub_scope.new_struct("RecordVisitor").generic(CAP_GENERIC);
@@ -61,8 +61,8 @@ impl SerdeImplGenerator {
         visitor_impl
             .new_fn("expecting")
             .arg_ref_self()
-            .arg("formatter", "&mut std::fmt::Formatter")
-            .ret("std::fmt::Result")
+            .arg("formatter", format!("&mut {}::fmt::Formatter", std))
+            .ret(format!("{}::fmt::Result", std))
             .line(format!(
                 "formatter.write_str(\"a {}\")",
                 record_spec.capped_record_name
@@ -115,7 +115,7 @@ impl SerdeImplGenerator {
         deserialize_fn.line("");
     }
 
-    fn generate_deserialize_impl(record_spec: &RecordSpec, scope: &mut Scope) {
+    fn generate_deserialize_impl(record_spec: &RecordSpec, std: &str, scope: &mut Scope) {
         let deserialize_impl = scope
             .new_impl(&record_spec.capped_record_name)
             .generic(&format!("'de, {}", CAP_GENERIC))
@@ -129,7 +129,7 @@ impl SerdeImplGenerator {
             .ret("Result<Self, D::Error>")
             .bound("D", "serde::Deserializer<'de>");
 
-        Self::generate_visitor(record_spec, deserialize_fn);
+        Self::generate_visitor(record_spec, std, deserialize_fn);
 
         deserialize_fn.line(format!(
             "deserializer.deserialize_tuple({}, RecordVisitor::<{}>)",
@@ -150,7 +150,7 @@ impl FragmentGenerator for SerdeImplGenerator {
 
         Self::generate_serialize_impl(record_spec, scope);
 
-        Self::generate_deserialize_impl(record_spec, scope);
+        Self::generate_deserialize_impl(record_spec, specs.std, scope);
     }
 }
 
@@ -164,7 +164,11 @@ mod tests {
 
     use super::*;
     use crate::{
-        generator::{config::GeneratorConfig, generate_variant, tests::assert_fragment_eq},
+        generator::{
+            config::GeneratorConfig,
+            generate_variant,
+            tests::{add_not_copy_integer, assert_fragment_eq},
+        },
         record::{
             definition::builder::native::NativeRecordDefinitionBuilder,
             type_resolver::HostTypeResolver,
@@ -181,7 +185,7 @@ mod tests {
             GeneratorConfig::new([Box::new(SerdeImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -190,7 +194,7 @@ mod tests {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -240,14 +244,14 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord0<CAP> {
             &scope.to_string(),
         );
 
-        assert_eq!(btreeset![], type_size_assertions);
+        assert_eq!(btreeset![], type_layout_assertions);
     }
 
     #[test]
     fn should_generate_serde_impl_with_data() {
         let mut builder = NativeRecordDefinitionBuilder::new(HostTypeResolver);
         builder.add_datum_allow_uninit::<u32, _>("integer").unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -255,7 +259,7 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord0<CAP> {
             GeneratorConfig::new([Box::new(SerdeImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         generate_variant(
             &definition,
@@ -264,7 +268,7 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -319,8 +323,12 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord0<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 
@@ -330,7 +338,7 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord0<CAP> {
         let i0 = builder
             .add_datum_allow_uninit::<u32, _>("integer0")
             .unwrap();
-        let nci0 = builder.add_datum::<u32, _>("not_copy_integer0").unwrap();
+        let nci0 = add_not_copy_integer(&mut builder, "not_copy_integer0");
         builder
             .add_datum_allow_uninit::<bool, _>("boolean1")
             .unwrap();
@@ -340,7 +348,7 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord0<CAP> {
         builder
             .add_datum_allow_uninit::<u32, _>("integer1")
             .unwrap();
-        builder.add_datum::<u32, _>("not_copy_integer1").unwrap();
+        add_not_copy_integer(&mut builder, "not_copy_integer1");
         builder.close_record_variant();
         let definition = builder.build();
 
@@ -348,7 +356,7 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord0<CAP> {
             GeneratorConfig::new([Box::new(SerdeImplGenerator) as Box<dyn FragmentGenerator>]);
 
         let mut scope = Scope::new();
-        let mut type_size_assertions = BTreeSet::new();
+        let mut type_layout_assertions = BTreeSet::new();
 
         let record0_spec = generate_variant(
             &definition,
@@ -357,10 +365,10 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord0<CAP> {
             None,
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
         let mut scope = Scope::new();
-        type_size_assertions.clear();
+        type_layout_assertions.clear();
         generate_variant(
             &definition,
             definition.max_type_align(),
@@ -368,7 +376,7 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord0<CAP> {
             Some(&record0_spec),
             &config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         assert_fragment_eq(
@@ -425,8 +433,12 @@ impl<'de, const CAP: usize> serde::Deserialize<'de> for CappedRecord1<CAP> {
         );
 
         assert_eq!(
-            btreeset![("u32", std::mem::size_of::<u32>())],
-            type_size_assertions
+            btreeset![(
+                "u32",
+                std::mem::size_of::<u32>(),
+                std::mem::align_of::<u32>()
+            )],
+            type_layout_assertions
         );
     }
 }
@@ -26,6 +26,8 @@ pub fn generate(
 ) -> String {
     let mut scope = Scope::new();
 
+    let std = std_crate(config);
+
     scope.import("truc_runtime::data", "RecordMaybeUninit");
 
     let mut uninit_type = Type::new("RecordMaybeUninit");
@@ -37,7 +39,8 @@ pub fn generate(
     scope.raw(format!(
         r#"/// Maximum size of the record, regardless of the record variant.
 ///
-/// Use that value, or a greater value, as the `CAP` const generic of every record.
+/// Use that value, or a greater value, as the `CAP` const generic of every record. A record may
+/// also use a tighter capacity as long as it is not smaller than its `VARIANT_SIZE`.
 pub const MAX_SIZE: usize = {};"#,
         max_size
     ));
@@ -55,12 +58,12 @@ It will never drop any data except the container by itself.
 
 # Intention
 
-This is to be used in custom allocators."#,
+This is to be used in custom allocators, see `truc_runtime::pool::RecordPool`."#,
     );
 
     let mut prev_record_spec: Option<RecordSpec> = None;
 
-    let mut type_size_assertions = BTreeSet::new();
+    let mut type_layout_assertions = BTreeSet::new();
 
     for variant in definition.variants() {
         let record_spec = generate_variant(
@@ -70,18 +73,28 @@ This is to be used in custom allocators."#,
             prev_record_spec.as_ref(),
             config,
             &mut scope,
-            &mut type_size_assertions,
+            &mut type_layout_assertions,
         );
 
         prev_record_spec = Some(record_spec);
     }
 
     // This checks there is no type substitution which could lead to unsafe
-    // code due to different type size.
-    for (type_name, size) in type_size_assertions {
+    // code due to different type size or alignment.
+    for (type_name, size, align) in type_layout_assertions {
+        scope.raw(format!(
+            "const _: () = {};",
+            const_assertion(
+                &format!("{}::mem::size_of::<{}>() == {}", std, type_name, size),
+                &format!("Size of {} is not {}", type_name, size),
+            )
+        ));
         scope.raw(format!(
-            "const_assert_eq!(std::mem::size_of::<{}>(), {});",
-            type_name, size
+            "const _: () = {};",
+            const_assertion(
+                &format!("{}::mem::align_of::<{}>() == {}", std, type_name, align),
+                &format!("Alignment of {} is not {}", type_name, align),
+            )
         ));
     }
 
@@ -98,7 +111,7 @@ pub fn generate_variant<'a>(
     prev_record_spec: Option<&RecordSpec>,
     config: &GeneratorConfig,
     scope: &mut Scope,
-    type_size_assertions: &mut BTreeSet<(&'a str, usize)>,
+    type_layout_assertions: &mut BTreeSet<(&'a str, usize, usize)>,
 ) -> RecordSpec<'a> {
     let data = variant
         .data_sorted()
@@ -140,12 +153,17 @@ pub fn generate_variant<'a>(
     };
 
     for datum in &record_spec.plus_data {
-        type_size_assertions.insert((datum.details().type_name(), datum.details().size()));
+        type_layout_assertions.insert((
+            datum.details().type_name(),
+            datum.details().size(),
+            datum.details().type_align(),
+        ));
     }
 
     let specs = FragmentGeneratorSpecs {
         record: &record_spec,
         prev_record: prev_record_spec,
+        std: std_crate(config),
     };
 
     let fragment_generators = config.fragment_generators.iter();
@@ -158,6 +176,30 @@ pub fn generate_variant<'a>(
     record_spec
 }
 
+fn std_crate(config: &GeneratorConfig) -> &'static str {
+    if config.no_std {
+        "core"
+    } else {
+        "std"
+    }
+}
+
+/// Generates a constant expression of type `()` which fails the compilation when `condition` is
+/// not met.
+///
+/// With the `msrv` feature, the generated code does not rely on panics in constants.
+fn const_assertion(condition: &str, message: &str) -> String {
+    if cfg!(feature = "msrv") {
+        format!("[()][!({}) as usize]", condition)
+    } else {
+        format!(
+            "assert!({}, {:?})",
+            condition,
+            message.replace('{', "{{").replace('}', "}}")
+        )
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 enum UninitKind<'a> {
     False,
@@ -206,6 +248,7 @@ fn generate_data_record(
     record_info: RecordInfo,
     data: &[&DatumDefinition<NativeDatumDetails>],
     uninit: UninitKind,
+    std: &str,
     scope: &mut Scope,
 ) {
     let record = scope.new_struct(record_info.name);
@@ -232,7 +275,7 @@ fn generate_data_record(
             (UninitKind::Safe { .. }, true) => {
                 record.field(
                     &format!("pub {}", datum.name()),
-                    format!("std::marker::PhantomData<T{}>", index),
+                    format!("{}::marker::PhantomData<T{}>", std, index),
                 );
             }
             (UninitKind::Unsafe, true) => {}
@@ -271,7 +314,7 @@ fn generate_data_record(
                 .map(|datum| if !datum.details().allow_uninit() {
                     format!("{}: from.{}", datum.name(), datum.name())
                 } else {
-                    format!("{}: std::marker::PhantomData", datum.name())
+                    format!("{}: {}::marker::PhantomData", datum.name(), std)
                 })
                 .join(", ")
         ));
@@ -316,12 +359,15 @@ mod tests {
     use super::*;
     use crate::record::{
         definition::{
-            builder::native::{DatumDefinitionOverride, NativeRecordDefinitionBuilder},
+            builder::native::{
+                DatumDefinitionOverride, NativeDatumError, NativeRecordDefinitionBuilder,
+            },
             DatumId,
         },
         type_resolver::{StaticTypeResolver, TypeResolver},
     };
 
+    /// Asserts both fragments are the same code.
     pub(crate) fn assert_fragment_eq(left: &str, right: &str) {
         let parsed_left = syn::parse_str::<File>(left).expect("left");
         let parsed_right = syn::parse_str::<File>(right).expect("right");
@@ -330,11 +376,49 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(not(feature = "msrv"))]
+    fn const_assertion_should_use_assert() {
+        assert_eq!(
+            const_assertion("CAP >= 4", "CAP of {Record} is too small"),
+            r#"assert!(CAP >= 4, "CAP of {{Record}} is too small")"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "msrv")]
+    fn const_assertion_should_index_an_array() {
+        assert_eq!(
+            const_assertion("CAP >= 4", "CAP of {Record} is too small"),
+            "[()][!(CAP >= 4) as usize]"
+        );
+    }
+
+    /// Adds a `u32` datum which is neither `Copy` nor trivially dropped as far as the generator
+    /// knows, so that every code path is exercised with a simple type.
+    pub(crate) fn add_not_copy_integer<R: TypeResolver>(
+        builder: &mut NativeRecordDefinitionBuilder<R>,
+        name: &str,
+    ) -> DatumId {
+        builder
+            .add_datum_override::<u32, _>(
+                name,
+                DatumDefinitionOverride {
+                    type_name: None,
+                    size: None,
+                    align: None,
+                    needs_drop: Some(true),
+                    allow_uninit: Some(false),
+                },
+            )
+            .unwrap()
+    }
+
     fn add_one<R: TypeResolver>(
         definition: &mut NativeRecordDefinitionBuilder<R>,
         rng: &mut rand_chacha::ChaCha8Rng,
         i: usize,
-    ) -> Result<DatumId, String> {
+    ) -> Result<DatumId, NativeDatumError> {
         match rng.gen_range(0..7) {
             0 => definition.add_datum_allow_uninit::<u8, _>(format!("field_{}", i)),
             1 => definition.add_datum_allow_uninit::<u16, _>(format!("field_{}", i)),
@@ -348,6 +432,7 @@ mod tests {
                     type_name: Some("Vec<usize>".to_owned()),
                     size: None,
                     align: None,
+                    needs_drop: None,
                     allow_uninit: None,
                 },
             ),
@@ -397,4 +482,34 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn no_std_generator() {
+        let type_resolver = {
+            let mut resolver = StaticTypeResolver::default();
+            resolver.add_all_types();
+            resolver
+        };
+
+        let mut definition = NativeRecordDefinitionBuilder::new(&type_resolver);
+        let removed = definition.add_datum::<u32, _>("removed").unwrap();
+        definition.add_datum::<u8, _>("kept").unwrap();
+        definition.close_record_variant();
+        definition.remove_datum(removed).unwrap();
+        definition.add_datum::<u64, _>("added").unwrap();
+        definition.close_record_variant();
+        let def = definition.build();
+        let code = generate(
+            &def,
+            &GeneratorConfig::default()
+                .with_common_fragments()
+                .with_unnamed_fields_fragments()
+                .with_clone_fragments()
+                .with_serde_fragments()
+                .with_no_std(),
+        );
+
+        assert!(!code.contains("std::"), "{}", code);
+        assert!(code.contains("core::"), "{}", code);
+    }
 }
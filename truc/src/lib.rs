@@ -78,7 +78,6 @@
 //! edition = "2021"
 //!
 //! [dependencies]
-//! static_assertions = "1"
 //! truc_runtime = { git = "https://github.com/arnodb/truc.git" }
 //!
 //! [build-dependencies]
@@ -89,6 +88,9 @@
 //!
 //! First of all you need a type resolver. If you are not cross-compiling then
 //! [HostTypeResolver](crate::record::type_resolver::HostTypeResolver) will work in most cases.
+//! When cross-compiling, the [build] module gives the resolver of the target.
+//! The target types can be computed on the host with [LayoutProbe](crate::build::layout::LayoutProbe),
+//! without running anything on the target.
 //!
 //! Then the definitions are built with
 //! [NativeRecordDefinitionBuilder](crate::record::definition::builder::native::NativeRecordDefinitionBuilder).
@@ -144,9 +146,6 @@
 //! ### Project implementation
 //!
 //! ```text
-//! #[macro_use]
-//! extern crate static_assertions;
-//!
 //! #[allow(dead_code)]
 //! #[allow(clippy::borrowed_box)]
 //! #[allow(clippy::module_inception)]
@@ -194,9 +193,13 @@ extern crate derive_new;
 #[macro_use]
 extern crate quote;
 
+pub mod build;
 pub mod generator;
 pub mod record;
 
+/// Re-export of the type probing crate used by the [add_type] macro.
+pub use truc_probe;
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
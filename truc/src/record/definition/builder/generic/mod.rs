@@ -123,6 +123,11 @@ impl<D> GenericRecordDefinitionBuilder<D> {
         variant_id
     }
 
+    /// Gives the ID of the variant being built, i.e. the next one to be closed.
+    pub(crate) fn building_variant_id(&self) -> RecordVariantId {
+        self.variants.len().into()
+    }
+
     /// Accesses datum definitions by ID.
     pub fn get_datum_definition(&self, id: DatumId) -> Option<&DatumDefinition<D>> {
         self.datum_definitions.get(id)
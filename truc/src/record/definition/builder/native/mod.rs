@@ -4,7 +4,10 @@
 //! when the type is used in many layers of the domain logic. In such a case the builder at
 //! [generic](super::generic) is a better choice.
 
-use std::ops::Index;
+use std::{
+    fmt::{Display, Formatter},
+    ops::Index,
+};
 
 use super::generic::{variant::RecordVariantBuilder, GenericRecordDefinitionBuilder};
 use crate::record::{
@@ -12,7 +15,8 @@ use crate::record::{
         DatumDefinition, DatumId, NativeDatumDetails, RecordDefinition, RecordVariant,
         RecordVariantId,
     },
-    type_resolver::TypeResolver,
+    type_name::{truc_dynamic_type_name, truc_type_name},
+    type_resolver::{DynamicTypeInfo, TypeResolver, UnknownTypeCause},
 };
 
 pub mod variant;
@@ -52,36 +56,47 @@ where
 
     /// Adds a new datum of type `T` to the current variant.
     ///
-    /// `T` does not need to be `Copy`, but if it is then consider using
-    /// [add_datum_allow_uninit](Self::add_datum_allow_uninit) instead.
-    pub fn add_datum<T, N>(&mut self, name: N) -> Result<DatumId, String>
+    /// Uninitialized values are allowed if the type resolver reports `T` as `Copy`. Only a
+    /// [StaticTypeResolver](crate::record::type_resolver::StaticTypeResolver) does so, for the
+    /// standard types and the types added with [add_type!](crate::add_type) or
+    /// [register_types!](crate::register_types). [HostTypeResolver](crate::record::type_resolver::HostTypeResolver) cannot probe
+    /// generic types and reports every type as not `Copy`, use
+    /// [add_datum_allow_uninit](Self::add_datum_allow_uninit) with it instead.
+    ///
+    /// Fails with [NativeDatumError::UnknownType] if the type resolver cannot resolve `T`.
+    pub fn add_datum<T, N>(&mut self, name: N) -> Result<DatumId, NativeDatumError>
     where
         N: Into<String>,
     {
-        self.inner.add_datum(
+        let name = name.into();
+        let type_info = self.resolve_type::<T>(&name)?;
+        self.add_datum_details(
             name,
             NativeDatumDetails {
                 offset: usize::MAX,
-                type_info: self.type_resolver.type_info::<T>(),
-                allow_uninit: false,
+                type_info: type_info.info,
+                allow_uninit: type_info.allow_uninit,
             },
         )
     }
 
     /// Adds a new datum of type `T: Copy` to the current variant.
     ///
-    /// `T` needs to be `Copy` to allow uninitialized values, if it is not `Copy` then consider
-    /// using [add_datum](Self::add_datum) instead.
-    pub fn add_datum_allow_uninit<T, N>(&mut self, name: N) -> Result<DatumId, String>
+    /// `T` needs to be `Copy` to allow uninitialized values. It is only useful when the type
+    /// resolver cannot tell whether `T` is `Copy` or not, e.g.
+    /// [HostTypeResolver](crate::record::type_resolver::HostTypeResolver).
+    pub fn add_datum_allow_uninit<T, N>(&mut self, name: N) -> Result<DatumId, NativeDatumError>
     where
         T: Copy,
         N: Into<String>,
     {
-        self.inner.add_datum(
+        let name = name.into();
+        let type_info = self.resolve_type::<T>(&name)?;
+        self.add_datum_details(
             name,
             NativeDatumDetails {
                 offset: usize::MAX,
-                type_info: self.type_resolver.type_info::<T>(),
+                type_info: type_info.info,
                 allow_uninit: true,
             },
         )
@@ -106,6 +121,8 @@ where
     ///         size: None,
     ///         // Same alignment rule
     ///         align: None,
+    ///         // Same drop requirement
+    ///         needs_drop: None,
     ///         // Same allow_uninit flag
     ///         allow_uninit: None,
     ///     },
@@ -115,16 +132,18 @@ where
         &mut self,
         name: N,
         datum_override: DatumDefinitionOverride,
-    ) -> Result<DatumId, String>
+    ) -> Result<DatumId, NativeDatumError>
     where
         N: Into<String>,
     {
-        self.inner.add_datum(
+        let name = name.into();
+        let type_info = self.resolve_type::<T>(&name)?;
+        self.add_datum_details(
             name,
             NativeDatumDetails {
                 offset: usize::MAX,
                 type_info: {
-                    let mut target_info = self.type_resolver.type_info::<T>();
+                    let mut target_info = type_info.info;
                     if let Some(type_name) = datum_override.type_name {
                         target_info.name = type_name;
                     }
@@ -134,21 +153,44 @@ where
                     if let Some(align) = datum_override.align {
                         target_info.align = align;
                     }
+                    if let Some(needs_drop) = datum_override.needs_drop {
+                        target_info.needs_drop = needs_drop;
+                    }
                     target_info
                 },
-                allow_uninit: datum_override.allow_uninit.unwrap_or(false),
+                allow_uninit: datum_override
+                    .allow_uninit
+                    .unwrap_or(type_info.allow_uninit),
             },
         )
     }
 
     /// Adds a new datum of dynamic type to the current variant.
-    pub fn add_dynamic_datum<T, N>(&mut self, name: N, r#type: T) -> Result<DatumId, String>
+    ///
+    /// With a [StaticTypeResolver](crate::record::type_resolver::StaticTypeResolver), generic and
+    /// composite types like `Vec<MyStruct>` are resolved without any override.
+    ///
+    /// Fails with [NativeDatumError::UnknownType] if the type resolver cannot resolve the type.
+    pub fn add_dynamic_datum<T, N>(
+        &mut self,
+        name: N,
+        r#type: T,
+    ) -> Result<DatumId, NativeDatumError>
     where
         T: AsRef<str>,
         N: Into<String>,
     {
-        let dynamic_type_info = self.type_resolver.dynamic_type_info(r#type.as_ref());
-        self.inner.add_datum(
+        let name = name.into();
+        let dynamic_type_info = self
+            .type_resolver
+            .try_dynamic_type_info(r#type.as_ref())
+            .map_err(|error| NativeDatumError::UnknownType {
+                datum: name.clone(),
+                variant: self.inner.building_variant_id(),
+                type_name: truc_dynamic_type_name(r#type.as_ref()),
+                cause: error.cause,
+            })?;
+        self.add_datum_details(
             name,
             NativeDatumDetails {
                 offset: usize::MAX,
@@ -162,8 +204,8 @@ where
     pub fn copy_datum(
         &mut self,
         datum: &DatumDefinition<NativeDatumDetails>,
-    ) -> Result<DatumId, String> {
-        self.inner.add_datum(
+    ) -> Result<DatumId, NativeDatumError> {
+        self.add_datum_details(
             datum.name(),
             NativeDatumDetails {
                 offset: usize::MAX,
@@ -173,6 +215,30 @@ where
         )
     }
 
+    fn resolve_type<T>(&self, name: &str) -> Result<DynamicTypeInfo, NativeDatumError> {
+        self.type_resolver
+            .try_type_info::<T>()
+            .map_err(|error| NativeDatumError::UnknownType {
+                datum: name.to_owned(),
+                variant: self.inner.building_variant_id(),
+                type_name: truc_type_name::<T>(),
+                cause: error.cause,
+            })
+    }
+
+    fn add_datum_details<N>(
+        &mut self,
+        name: N,
+        details: NativeDatumDetails,
+    ) -> Result<DatumId, NativeDatumError>
+    where
+        N: Into<String>,
+    {
+        self.inner
+            .add_datum(name, details)
+            .map_err(NativeDatumError::Definition)
+    }
+
     /// Remove a datum from the current variant.
     pub fn remove_datum(&mut self, datum_id: DatumId) -> Result<(), String> {
         self.inner.remove_datum(datum_id)
@@ -255,10 +321,50 @@ pub struct DatumDefinitionOverride {
     pub size: Option<usize>,
     /// Use it to override the type alignment.
     pub align: Option<usize>,
+    /// Use it to override the type drop requirement.
+    pub needs_drop: Option<bool>,
     /// Use it to override the `allow_uninit` flag.
     pub allow_uninit: Option<bool>,
 }
 
+/// Errors of [NativeRecordDefinitionBuilder] when adding datums.
+#[derive(PartialEq, Eq, Debug)]
+pub enum NativeDatumError {
+    /// The type of a datum could not be resolved by the type resolver.
+    UnknownType {
+        /// Name of the datum.
+        datum: String,
+        /// ID of the variant being built.
+        variant: RecordVariantId,
+        /// Name of the type.
+        type_name: String,
+        /// Why the type could not be resolved.
+        cause: UnknownTypeCause,
+    },
+    /// The datum could not be added to the definition.
+    Definition(String),
+}
+
+impl Display for NativeDatumError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownType {
+                datum,
+                variant,
+                type_name,
+                cause,
+            } => write!(
+                f,
+                "Could not resolve type {} of datum {} in variant {}: {}",
+                type_name, datum, variant, cause
+            ),
+            Self::Definition(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for NativeDatumError {}
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
@@ -271,21 +377,23 @@ mod tests {
 
     use super::{
         variant::{self},
-        NativeRecordDefinitionBuilder,
+        NativeDatumError, NativeRecordDefinitionBuilder,
     };
     use crate::record::{
         definition::{
             builder::generic::variant::RecordVariantBuilder, DatumDefinition, DatumId,
             NativeDatumDetails,
         },
-        type_resolver::{HostTypeResolver, TypeInfo, TypeResolver},
+        type_resolver::{
+            HostTypeResolver, StaticTypeResolver, TypeInfo, TypeResolver, UnknownTypeCause,
+        },
     };
 
     fn add_one<R: TypeResolver>(
         definition: &mut NativeRecordDefinitionBuilder<R>,
         rng: &mut rand_chacha::ChaCha8Rng,
         i: usize,
-    ) -> Result<DatumId, String> {
+    ) -> Result<DatumId, NativeDatumError> {
         match rng.gen_range(0..4) {
             0 => definition.add_datum::<u8, _>(format!("field_{}", i)),
             1 => definition.add_datum::<u16, _>(format!("field_{}", i)),
@@ -459,6 +567,7 @@ mod tests {
                         name: "foo".to_owned(),
                         size: 3,
                         align: 5,
+                        needs_drop: true,
                     },
                     allow_uninit: true,
                 },
@@ -474,6 +583,7 @@ mod tests {
                         name: "foo".to_owned(),
                         size: 11,
                         align: 13,
+                        needs_drop: true,
                     },
                     allow_uninit: false,
                 },
@@ -493,6 +603,7 @@ mod tests {
                         name: "foo".to_owned(),
                         size: 3,
                         align: 5,
+                        needs_drop: true,
                     },
                     allow_uninit: true,
                 },
@@ -512,6 +623,7 @@ mod tests {
                         name: "foo".to_owned(),
                         size: 11,
                         align: 13,
+                        needs_drop: true,
                     },
                     allow_uninit: false,
                 },
@@ -520,6 +632,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_allow_uninit_copy_types() {
+        let type_resolver = {
+            let mut resolver = StaticTypeResolver::new();
+            resolver.add_std_types();
+            resolver
+        };
+        let mut definition = NativeRecordDefinitionBuilder::new(&type_resolver);
+        let copy_id = definition.add_datum::<u32, _>("copy").unwrap();
+        let not_copy_id = definition.add_datum::<String, _>("not_copy").unwrap();
+        definition.close_record_variant();
+
+        assert!(definition[copy_id].details().allow_uninit());
+        assert!(!definition[copy_id].details().needs_drop());
+        assert!(!definition[not_copy_id].details().allow_uninit());
+        assert!(definition[not_copy_id].details().needs_drop());
+    }
+
+    #[test]
+    fn should_not_allow_uninit_host_types() {
+        let mut definition = NativeRecordDefinitionBuilder::new(HostTypeResolver);
+        let copy_id = definition.add_datum::<u32, _>("copy").unwrap();
+        let forced_id = definition
+            .add_datum_allow_uninit::<u32, _>("forced")
+            .unwrap();
+        definition.close_record_variant();
+
+        assert!(!definition[copy_id].details().allow_uninit());
+        assert!(definition[forced_id].details().allow_uninit());
+    }
+
     #[test]
     fn should_remove_datum_added_in_first_variant() {
         let type_resolver = HostTypeResolver;
@@ -544,4 +687,59 @@ mod tests {
         assert!(def.variants().next().is_some());
         assert_eq!(0, def.variants().next().unwrap().data_len());
     }
+
+    #[test]
+    fn should_report_unknown_types() {
+        struct Unknown;
+
+        let mut type_resolver = StaticTypeResolver::new();
+        type_resolver.add_std_types();
+        let mut definition = NativeRecordDefinitionBuilder::new(&type_resolver);
+        definition.add_datum::<u32, _>("uint_32").unwrap();
+        definition.close_record_variant();
+
+        let error = definition.add_datum::<Unknown, _>("unknown").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Could not resolve type {} of datum unknown in variant 1: unknown type",
+                std::any::type_name::<Unknown>().replace("::", " :: ")
+            )
+        );
+
+        let error = definition
+            .add_dynamic_datum("dynamic", "my_crate::Unknown")
+            .unwrap_err();
+        assert_eq!(
+            error,
+            NativeDatumError::UnknownType {
+                datum: "dynamic".to_owned(),
+                variant: 1.into(),
+                type_name: "my_crate :: Unknown".to_owned(),
+                cause: UnknownTypeCause::NotFound,
+            }
+        );
+
+        let error = definition
+            .add_dynamic_datum("dynamic", "Option<my_crate::Unknown>")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Could not resolve type Option < my_crate :: Unknown > of datum dynamic in variant 1: \
+             unresolved argument my_crate :: Unknown: unknown type"
+        );
+
+        let error = definition.add_datum::<u32, _>("uint_32").unwrap_err();
+        assert_eq!(
+            error,
+            NativeDatumError::Definition(
+                "Field with name uint_32 already exists in current variant".to_owned()
+            )
+        );
+
+        let error = NativeRecordDefinitionBuilder::new(HostTypeResolver)
+            .add_dynamic_datum("dynamic", "u32")
+            .unwrap_err();
+        assert_matches!(error, NativeDatumError::UnknownType { .. });
+    }
 }
@@ -355,6 +355,7 @@ mod tests {
                     name: type_name::<T>().to_owned(),
                     size: std::mem::size_of::<T>(),
                     align: std::mem::align_of::<T>(),
+                    needs_drop: std::mem::needs_drop::<T>(),
                 },
                 true,
             ),
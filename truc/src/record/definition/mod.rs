@@ -262,6 +262,11 @@ impl NativeDatumDetails {
         self.type_info.align
     }
 
+    /// Gets the type drop requirement of this datum.
+    pub fn needs_drop(&self) -> bool {
+        self.type_info.needs_drop
+    }
+
     /// Gets the type `allow_uninit` flag of this datum.
     pub fn allow_uninit(&self) -> bool {
         self.allow_uninit
@@ -0,0 +1,982 @@
+//! Type resolution tools.
+
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    fmt::{Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::record::type_name::{truc_dynamic_type_name, truc_type_name};
+
+mod rules;
+
+/// Type information (name, size, align and drop requirement) as given by the Rust compiler.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct TypeInfo {
+    /// The type name as it can be used in Rust code.
+    pub name: String,
+    /// The type size given by `std::mem::size_of()`.
+    pub size: usize,
+    /// The type size given by `std::mem::align_of()`.
+    pub align: usize,
+    /// The drop requirement given by `std::mem::needs_drop()`.
+    ///
+    /// It defaults to `true` when missing from serialized data, which is always safe.
+    #[serde(default = "default_needs_drop")]
+    pub needs_drop: bool,
+}
+
+fn default_needs_drop() -> bool {
+    true
+}
+
+/// Additional type information.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct DynamicTypeInfo {
+    /// Rust type information.
+    pub info: TypeInfo,
+    /// Indicates whether or not the type can be left safely uninitialized. Merely `Copy` types
+    /// can be left uninitialized, any other type cannot.
+    pub allow_uninit: bool,
+}
+
+/// Error of a type resolver which cannot resolve a type.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct UnknownType {
+    /// Name of the type.
+    pub type_name: String,
+    /// Why the type cannot be resolved.
+    pub cause: UnknownTypeCause,
+}
+
+impl UnknownType {
+    /// Creates the error of a type which is not known at all.
+    pub fn not_found<N>(type_name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            type_name: type_name.into(),
+            cause: UnknownTypeCause::NotFound,
+        }
+    }
+}
+
+impl Display for UnknownType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.type_name, self.cause)
+    }
+}
+
+impl std::error::Error for UnknownType {}
+
+/// Cause of an [UnknownType] error.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum UnknownTypeCause {
+    /// The type is not known by the resolver and no rule applies to it.
+    NotFound,
+    /// A generic argument, an element or a field of the type cannot be resolved.
+    Argument(Box<UnknownType>),
+    /// A rule applies to the type but the reference type it is computed from, e.g. `usize` for
+    /// pointers, cannot be resolved.
+    Reference(Box<UnknownType>),
+    /// A rule applies to the type but cannot compute its layout, e.g. an `Option` of a type whose
+    /// niches are unknown.
+    Layout,
+}
+
+impl Display for UnknownTypeCause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "unknown type"),
+            Self::Argument(argument) => write!(f, "unresolved argument {}", argument),
+            Self::Reference(reference) => write!(f, "unresolved reference type {}", reference),
+            Self::Layout => write!(f, "layout cannot be computed"),
+        }
+    }
+}
+
+/// Abstract type resolver trait.
+///
+/// Unresolved types are reported as [UnknownType] errors by the `try_` methods, while
+/// [type_info](Self::type_info) and [dynamic_type_info](Self::dynamic_type_info) panic.
+pub trait TypeResolver {
+    /// Gives the Rust type information for `T`, along with whether it is `Copy` or not (see
+    /// [DynamicTypeInfo::allow_uninit]).
+    fn try_type_info<T>(&self) -> Result<DynamicTypeInfo, UnknownType>;
+
+    /// Gives the dynamic type information `type_name`.
+    fn try_dynamic_type_info(&self, type_name: &str) -> Result<DynamicTypeInfo, UnknownType>;
+
+    /// Gives the Rust type information for `T`, along with whether it is `Copy` or not (see
+    /// [DynamicTypeInfo::allow_uninit]).
+    ///
+    /// Panics if the type cannot be resolved, see [try_type_info](Self::try_type_info).
+    fn type_info<T>(&self) -> DynamicTypeInfo {
+        self.try_type_info::<T>()
+            .unwrap_or_else(|error| panic!("Could not resolve type {}", error))
+    }
+
+    /// Gives the dynamic type information `type_name`.
+    ///
+    /// Panics if the type cannot be resolved, see
+    /// [try_dynamic_type_info](Self::try_dynamic_type_info).
+    fn dynamic_type_info(&self, type_name: &str) -> DynamicTypeInfo {
+        self.try_dynamic_type_info(type_name)
+            .unwrap_or_else(|error| panic!("Could not resolve type {}", error))
+    }
+
+    /// Combines this resolver with `fallback` which is tried when this resolver cannot resolve a
+    /// type.
+    ///
+    /// ```rust
+    /// # use truc::record::type_resolver::{HostTypeResolver, StaticTypeResolver, TypeResolver};
+    /// #
+    /// let mut std_types = StaticTypeResolver::new();
+    /// std_types.add_std_types();
+    ///
+    /// let resolver = std_types.with_fallback(HostTypeResolver);
+    ///
+    /// // Resolved by the static resolver which knows `u32` is `Copy`
+    /// assert!(resolver.type_info::<u32>().allow_uninit);
+    /// // Resolved by the host resolver
+    /// assert_eq!(resolver.type_info::<std::time::Duration>().info.size, 16);
+    /// ```
+    fn with_fallback<R>(self, fallback: R) -> LayeredTypeResolver<Self, R>
+    where
+        Self: Sized,
+        R: TypeResolver,
+    {
+        LayeredTypeResolver::new(self, fallback)
+    }
+}
+
+impl<R> TypeResolver for &R
+where
+    R: TypeResolver,
+{
+    fn try_type_info<T>(&self) -> Result<DynamicTypeInfo, UnknownType> {
+        R::try_type_info::<T>(self)
+    }
+
+    fn try_dynamic_type_info(&self, type_name: &str) -> Result<DynamicTypeInfo, UnknownType> {
+        R::try_dynamic_type_info(self, type_name)
+    }
+}
+
+/// A type resolver that can give Rust type information only. Dynamic types are never resolved.
+///
+/// Since `Copy` cannot be probed on generic types, all types are reported as not `Copy`: use a
+/// [StaticTypeResolver] with [add_type!](crate::add_type) to probe concrete types.
+pub struct HostTypeResolver;
+
+impl TypeResolver for HostTypeResolver {
+    /// Resolves the Rust type information by calling `std::mem::size_of()`,
+    /// `std::mem::align_of()` and `std::mem::needs_drop()`.
+    fn try_type_info<T>(&self) -> Result<DynamicTypeInfo, UnknownType> {
+        Ok(DynamicTypeInfo {
+            info: host_type_info::<T>(),
+            allow_uninit: false,
+        })
+    }
+
+    /// Always fails with [UnknownTypeCause::NotFound].
+    fn try_dynamic_type_info(&self, type_name: &str) -> Result<DynamicTypeInfo, UnknownType> {
+        Err(UnknownType::not_found(truc_dynamic_type_name(type_name)))
+    }
+}
+
+/// A type resolver trying a first resolver, then a fallback resolver when the first one cannot
+/// resolve a type.
+///
+/// Resolvers can be layered as much as needed, e.g. explicit overrides, then precomputed types,
+/// then the host:
+///
+/// ```rust
+/// # use truc::record::type_resolver::{
+/// #     DynamicTypeInfo, HostTypeResolver, StaticTypeResolver, TypeInfo, TypeResolver,
+/// # };
+/// #
+/// let mut overrides = StaticTypeResolver::new();
+/// overrides.add_dynamic_type(DynamicTypeInfo {
+///     info: TypeInfo {
+///         name: "ffi::Handle".to_owned(),
+///         size: 8,
+///         align: 8,
+///         needs_drop: true,
+///     },
+///     allow_uninit: false,
+/// });
+///
+/// let mut std_types = StaticTypeResolver::new();
+/// std_types.add_std_types();
+///
+/// let resolver = overrides
+///     .with_fallback(std_types)
+///     .with_fallback(HostTypeResolver);
+///
+/// assert_eq!(resolver.dynamic_type_info("ffi::Handle").info.size, 8);
+/// assert!(resolver.dynamic_type_info("u32").allow_uninit);
+/// ```
+///
+/// Each resolver resolves types on its own: rules of a [StaticTypeResolver] are not applied to
+/// types known by another resolver.
+#[derive(Debug)]
+pub struct LayeredTypeResolver<F, B> {
+    first: F,
+    fallback: B,
+}
+
+impl<F, B> LayeredTypeResolver<F, B>
+where
+    F: TypeResolver,
+    B: TypeResolver,
+{
+    /// Creates a resolver trying `first`, then `fallback`.
+    pub fn new(first: F, fallback: B) -> Self {
+        Self { first, fallback }
+    }
+}
+
+impl<F, B> TypeResolver for LayeredTypeResolver<F, B>
+where
+    F: TypeResolver,
+    B: TypeResolver,
+{
+    /// Fails with the error of the fallback if neither resolver can resolve `T`, or with the error
+    /// of the first resolver if the fallback does not know `T` at all.
+    fn try_type_info<T>(&self) -> Result<DynamicTypeInfo, UnknownType> {
+        self.first.try_type_info::<T>().or_else(|first_error| {
+            self.fallback
+                .try_type_info::<T>()
+                .map_err(|error| most_precise(first_error, error))
+        })
+    }
+
+    /// Fails with the error of the fallback if neither resolver can resolve `type_name`, or with
+    /// the error of the first resolver if the fallback does not know `type_name` at all.
+    fn try_dynamic_type_info(&self, type_name: &str) -> Result<DynamicTypeInfo, UnknownType> {
+        self.first
+            .try_dynamic_type_info(type_name)
+            .or_else(|first_error| {
+                self.fallback
+                    .try_dynamic_type_info(type_name)
+                    .map_err(|error| most_precise(first_error, error))
+            })
+    }
+}
+
+fn most_precise(first_error: UnknownType, fallback_error: UnknownType) -> UnknownType {
+    if fallback_error.cause == UnknownTypeCause::NotFound {
+        first_error
+    } else {
+        fallback_error
+    }
+}
+
+fn host_type_info<T>() -> TypeInfo {
+    TypeInfo {
+        name: truc_type_name::<T>(),
+        size: std::mem::size_of::<T>(),
+        align: std::mem::align_of::<T>(),
+        needs_drop: std::mem::needs_drop::<T>(),
+    }
+}
+
+/// Adds a type to a [StaticTypeResolver], probing whether it is `Copy` or not.
+///
+/// ```rust
+/// # use truc::record::type_resolver::StaticTypeResolver;
+/// #
+/// let mut resolver = StaticTypeResolver::new();
+/// truc::add_type!(resolver, (u32, char));
+/// truc::add_type!(resolver, Vec<u32>);
+/// ```
+#[macro_export]
+macro_rules! add_type {
+    ($resolver:expr, $type:ty) => {
+        $resolver.add_probed_type::<$type>($crate::truc_probe::is_copy!($type))
+    };
+}
+
+/// Defines a function adding a list of types to a [StaticTypeResolver] with [crate::add_type!].
+///
+/// The same list can then be used by a build script and by the binary computing the types of a
+/// target, see [StaticTypeResolver::target_types_json].
+///
+/// ```rust
+/// # use truc::record::type_resolver::{StaticTypeResolver, TypeResolver};
+/// #
+/// #[derive(Clone, Copy)]
+/// pub struct Point(pub f32, pub f32);
+///
+/// pub struct Name(pub String);
+///
+/// truc::register_types! {
+///     /// Adds the types of the crate to a resolver.
+///     pub fn register_types(Point, Name);
+/// }
+///
+/// let mut resolver = StaticTypeResolver::new();
+/// register_types(&mut resolver);
+/// assert!(resolver.type_info::<Point>().allow_uninit);
+/// assert!(!resolver.type_info::<Name>().allow_uninit);
+/// ```
+#[macro_export]
+macro_rules! register_types {
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($($type:ty),* $(,)?);) => {
+        $(#[$attr])*
+        $vis fn $name(resolver: &mut $crate::record::type_resolver::StaticTypeResolver) {
+            $($crate::add_type!(resolver, $type);)*
+        }
+    };
+}
+
+macro_rules! add_type_and_option {
+    ($resolver:ident, $type:ty) => {
+        add_type!($resolver, $type);
+        add_type!($resolver, Option<$type>);
+    };
+}
+macro_rules! add_type_and_arrays {
+    ($resolver:ident, $type:ty) => {
+        add_type_and_option!($resolver, $type);
+        add_type_and_option!($resolver, [$type; 1]);
+        add_type_and_option!($resolver, [$type; 2]);
+        add_type_and_option!($resolver, [$type; 3]);
+        add_type_and_option!($resolver, [$type; 4]);
+        add_type_and_option!($resolver, [$type; 5]);
+        add_type_and_option!($resolver, [$type; 6]);
+        add_type_and_option!($resolver, [$type; 7]);
+        add_type_and_option!($resolver, [$type; 8]);
+        add_type_and_option!($resolver, [$type; 9]);
+        add_type_and_option!($resolver, [$type; 10]);
+    };
+}
+
+/// A type resolved that loads precomputed type information.
+///
+/// In addition to allowing a good level of customization, it is also very useful for
+/// cross-compilation:
+///
+/// * compute data by running the resolution on the target platform
+/// * serialize the data with `serde` to a file
+/// * deserialize the file in the project to be cross-compiled
+///
+/// Serialized data has a header, when set, recording the target, its pointer width and the
+/// compiler version (see [TypesHeader]), so that data computed for another target or compiler can
+/// be refused. Data computed separately, e.g. per crate, can be merged with
+/// [merge](StaticTypeResolver::merge).
+///
+/// Types which are not known are resolved by rules when possible: vectors, strings, boxes, `Arc`,
+/// `Rc` and references of any type, arrays of any length, tuples and options of types which can be
+/// resolved. Rules rely on the layouts of `usize`, `String` and `Vec<()>` which are part of the
+/// standard types.
+///
+/// ```rust
+/// # use truc::record::type_resolver::{StaticTypeResolver, TypeResolver};
+/// #
+/// let mut resolver = StaticTypeResolver::new();
+/// resolver.add_std_types();
+///
+/// let info = resolver.dynamic_type_info("Vec<my_crate::MyStruct>");
+/// assert_eq!(info.info.size, std::mem::size_of::<Vec<()>>());
+///
+/// let info = resolver.dynamic_type_info("Option<[(u64, bool); 42]>");
+/// assert_eq!(info.info.size, std::mem::size_of::<Option<[(u64, bool); 42]>>());
+/// assert!(info.allow_uninit);
+/// ```
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "StaticTypeResolverRepr")]
+pub struct StaticTypeResolver {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    header: Option<TypesHeader>,
+    types: BTreeMap<String, DynamicTypeInfo>,
+}
+
+/// Header of serialized type information, telling how it was computed.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct TypesHeader {
+    /// Target triple.
+    pub target: String,
+    /// Pointer width in bits.
+    pub pointer_width: u32,
+    /// Version of the Rust compiler as given by `rustc -V`.
+    pub rustc_version: String,
+}
+
+impl TypesHeader {
+    /// Gives the header of the current target and compiler, i.e. the ones `truc` was compiled
+    /// with.
+    pub fn current() -> Self {
+        Self {
+            target: env!("TRUC_TARGET").to_owned(),
+            pointer_width: usize::BITS,
+            rustc_version: env!("TRUC_RUSTC_VERSION").to_owned(),
+        }
+    }
+}
+
+impl Display for TypesHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "target {} ({} bits) with {}",
+            self.target, self.pointer_width, self.rustc_version
+        )
+    }
+}
+
+/// Serialized type information, with a header or as a bare map of types like in former versions.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StaticTypeResolverRepr {
+    WithHeader(StaticTypeResolverWithHeader),
+    Bare(BTreeMap<String, DynamicTypeInfo>),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StaticTypeResolverWithHeader {
+    #[serde(default)]
+    header: Option<TypesHeader>,
+    types: BTreeMap<String, DynamicTypeInfo>,
+}
+
+impl From<StaticTypeResolverRepr> for StaticTypeResolver {
+    fn from(repr: StaticTypeResolverRepr) -> Self {
+        match repr {
+            StaticTypeResolverRepr::WithHeader(StaticTypeResolverWithHeader { header, types }) => {
+                Self { header, types }
+            }
+            StaticTypeResolverRepr::Bare(types) => Self::from(types),
+        }
+    }
+}
+
+impl From<BTreeMap<String, DynamicTypeInfo>> for StaticTypeResolver {
+    /// Creates a resolver without header.
+    fn from(types: BTreeMap<String, DynamicTypeInfo>) -> Self {
+        Self {
+            header: None,
+            types,
+        }
+    }
+}
+
+/// Errors when merging [StaticTypeResolver]s.
+#[derive(PartialEq, Eq, Debug)]
+pub enum MergeError {
+    /// Type information was not computed for the same target or compiler.
+    HeaderConflict {
+        /// Header of the resolver merged into.
+        ours: TypesHeader,
+        /// Header of the merged resolver.
+        theirs: TypesHeader,
+    },
+    /// A type is known by both resolvers with different information.
+    TypeConflict {
+        /// Type information of the resolver merged into.
+        ours: DynamicTypeInfo,
+        /// Type information of the merged resolver.
+        theirs: DynamicTypeInfo,
+    },
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::HeaderConflict { ours, theirs } => write!(
+                f,
+                "Type information computed for {} cannot be merged with type information computed for {}",
+                theirs, ours
+            ),
+            Self::TypeConflict { ours, theirs } => write!(
+                f,
+                "Type {} is defined with {:?} and {:?}",
+                ours.info.name, ours, theirs
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+impl StaticTypeResolver {
+    /// Creates an empty resolver.
+    pub fn new() -> Self {
+        Self {
+            header: None,
+            types: BTreeMap::new(),
+        }
+    }
+
+    /// Gives the header telling how type information was computed, if known.
+    pub fn header(&self) -> Option<&TypesHeader> {
+        self.header.as_ref()
+    }
+
+    /// Sets the header telling how type information was computed.
+    pub fn set_header(&mut self, header: TypesHeader) {
+        self.header = Some(header);
+    }
+
+    /// Merges the type information of `other`, e.g. computed for another crate.
+    ///
+    /// Types known by both resolvers must have the same information, and headers must be the same
+    /// if both are known. Nothing is merged on error.
+    pub fn merge(&mut self, other: StaticTypeResolver) -> Result<(), MergeError> {
+        if let (Some(ours), Some(theirs)) = (&self.header, &other.header) {
+            if ours != theirs {
+                return Err(MergeError::HeaderConflict {
+                    ours: ours.clone(),
+                    theirs: theirs.clone(),
+                });
+            }
+        }
+        for (type_name, theirs) in &other.types {
+            if let Some(ours) = self.types.get(type_name) {
+                if ours != theirs {
+                    return Err(MergeError::TypeConflict {
+                        ours: ours.clone(),
+                        theirs: theirs.clone(),
+                    });
+                }
+            }
+        }
+        if self.header.is_none() {
+            self.header = other.header;
+        }
+        self.types.extend(other.types);
+        Ok(())
+    }
+
+    /// Adds a single type information to the data, `is_copy` being the result of
+    /// [truc_probe::is_copy].
+    ///
+    /// Use the [crate::add_type!] macro to probe the type automatically.
+    pub fn add_probed_type<T>(&mut self, is_copy: bool) {
+        self.add_type_info(DynamicTypeInfo {
+            info: host_type_info::<T>(),
+            allow_uninit: is_copy,
+        });
+    }
+
+    /// Adds a single type information to the data, e.g. to override the information of a type
+    /// which cannot be computed, the name being normalized.
+    pub fn add_dynamic_type(&mut self, mut info: DynamicTypeInfo) {
+        info.info.name = truc_dynamic_type_name(&info.info.name);
+        self.add_type_info(info);
+    }
+
+    fn add_type_info(&mut self, info: DynamicTypeInfo) {
+        match self.types.entry(info.info.name.clone()) {
+            Entry::Vacant(vacant) => {
+                vacant.insert(info);
+            }
+            Entry::Occupied(occupied) => {
+                panic!(
+                    "Type {} is already defined with {:?}",
+                    info.info.name,
+                    occupied.get()
+                );
+            }
+        }
+    }
+
+    /// Adds a single non `Copy` type information to the data.
+    ///
+    /// Use the [crate::add_type!] macro to probe the type automatically.
+    pub fn add_type<T>(&mut self) {
+        self.add_probed_type::<T>(false);
+    }
+
+    /// Adds a single `Copy` type information to the data.
+    ///
+    /// Use the [crate::add_type!] macro to probe the type automatically.
+    pub fn add_type_allow_uninit<T>(&mut self)
+    where
+        T: Copy,
+    {
+        self.add_probed_type::<T>(true);
+    }
+
+    /// Adds standard types to the data.
+    ///
+    /// It includes:
+    ///
+    /// * various types of integers and floating point numbers
+    /// * `String`
+    /// * `Box<str>`
+    /// * `Vec<()>` which is enough to support any kind of vector
+    pub fn add_std_types(&mut self) {
+        add_type_and_arrays!(self, u8);
+        add_type_and_arrays!(self, u16);
+        add_type_and_arrays!(self, u32);
+        add_type_and_arrays!(self, u64);
+        add_type_and_arrays!(self, u128);
+        add_type_and_arrays!(self, usize);
+
+        add_type_and_arrays!(self, i8);
+        add_type_and_arrays!(self, i16);
+        add_type_and_arrays!(self, i32);
+        add_type_and_arrays!(self, i64);
+        add_type_and_arrays!(self, i128);
+        add_type_and_arrays!(self, isize);
+
+        add_type_and_arrays!(self, f32);
+        add_type_and_arrays!(self, f64);
+
+        add_type_and_arrays!(self, char);
+
+        add_type_and_arrays!(self, bool);
+
+        add_type_and_arrays!(self, String);
+        add_type_and_arrays!(self, Box<str>);
+
+        add_type_and_arrays!(self, Vec<()>);
+    }
+
+    /// Adds `Uuid` types to the data.
+    #[cfg(feature = "uuid")]
+    pub fn add_uuid_types(&mut self) {
+        add_type_and_arrays!(self, uuid::Uuid);
+    }
+
+    /// Adds all known types to the data.
+    ///
+    /// Basically all standard types plus feature gated types like `Uuid` types.
+    pub fn add_all_types(&mut self) {
+        self.add_std_types();
+        #[cfg(feature = "uuid")]
+        self.add_uuid_types();
+    }
+
+    /// Gives the names of all the types of the data.
+    pub fn type_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.types.keys().map(String::as_str)
+    }
+
+    /// Gives the pretty printed JSON of all known types and the types added by `register`, e.g. a
+    /// function defined with [crate::register_types!], as seen by the current target.
+    ///
+    /// This is meant to be called by a binary running on the target platform in order to prepare
+    /// cross-compilation.
+    pub fn target_types_json<F>(register: F) -> Result<String, serde_json::Error>
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut resolver = Self::new();
+        resolver.set_header(TypesHeader::current());
+        resolver.add_all_types();
+        register(&mut resolver);
+        resolver.to_json_string_pretty()
+    }
+
+    /// Serialization to a `serde_json::Value`, with the header if known.
+    pub fn to_json_value(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    /// Serialization to a `String`, with the header if known.
+    pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Serialization to a `String` with pretty printing, with the header if known.
+    pub fn to_json_string_pretty(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl Default for StaticTypeResolver {
+    /// Creates an empty resolver.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeResolver for StaticTypeResolver {
+    /// Gives the Rust type information for `T` by looking up loaded data or applying rules.
+    fn try_type_info<T>(&self) -> Result<DynamicTypeInfo, UnknownType> {
+        rules::resolve(&self.types, &truc_type_name::<T>())
+    }
+
+    /// Gives the dynamic type information  for `type_name` by looking up data or applying rules.
+    fn try_dynamic_type_info(&self, type_name: &str) -> Result<DynamicTypeInfo, UnknownType> {
+        rules::resolve(&self.types, &truc_dynamic_type_name(type_name))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_type_resolver() {
+        let mut type_infos = StaticTypeResolver::default();
+
+        type_infos.add_all_types();
+
+        let json = type_infos.to_json_value().unwrap();
+        type_infos.to_json_string().unwrap();
+        type_infos.to_json_string_pretty().unwrap();
+
+        assert!(json.get("header").is_none());
+        let types = json["types"].as_object().unwrap();
+
+        for t in [
+            "usize",
+            "isize",
+            "f32",
+            "f64",
+            "char",
+            "bool",
+            "String",
+            "Box < str >",
+            "Vec < () >",
+        ] {
+            assert!(types.contains_key(&t.to_owned()), "{}", t);
+            assert!(types.contains_key(&format!("[{} ; 2]", t)), "[{} ; 2]", t);
+            assert!(types.contains_key(&format!("[{} ; 10]", t)), "[{} ; 10]", t);
+        }
+
+        let name = assert_matches!(
+            type_infos.type_info::<usize>(),
+            DynamicTypeInfo {
+                info: TypeInfo {
+                    name,
+                    size: _,
+                    align: _,
+                    needs_drop: false,
+                },
+                allow_uninit: true,
+            } => name
+        );
+        assert_eq!(name, "usize");
+
+        let name = assert_matches!(
+            type_infos.dynamic_type_info("isize"),
+            DynamicTypeInfo {
+                info: TypeInfo {
+                    name,
+                    size: _,
+                    align: _,
+                    needs_drop: false,
+                },
+                allow_uninit: true,
+            } => name
+        );
+        assert_eq!(name, "isize");
+
+        let name = assert_matches!(
+            type_infos.dynamic_type_info("String"),
+            DynamicTypeInfo {
+                info: TypeInfo {
+                    name,
+                    size: _,
+                    align: _,
+                    needs_drop: true,
+                },
+                allow_uninit: false,
+            } => name
+        );
+        assert_eq!(name, "String");
+    }
+
+    #[test]
+    fn test_type_added_twice() {
+        let mut type_infos = StaticTypeResolver::default();
+
+        type_infos.add_type::<usize>();
+
+        let result = std::panic::catch_unwind(move || type_infos.add_type::<usize>());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_type_added_twice_allow_uninit() {
+        let mut type_infos = StaticTypeResolver::default();
+
+        type_infos.add_type_allow_uninit::<usize>();
+
+        let result = std::panic::catch_unwind(move || type_infos.add_type_allow_uninit::<usize>());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_add_type_probes_copy() {
+        #[derive(Clone, Copy)]
+        struct CopyStruct;
+
+        #[derive(Clone)]
+        struct CloneStruct;
+
+        let mut type_infos = StaticTypeResolver::default();
+
+        add_type!(type_infos, CopyStruct);
+        add_type!(type_infos, CloneStruct);
+
+        assert!(type_infos.type_info::<CopyStruct>().allow_uninit);
+        assert!(!type_infos.type_info::<CloneStruct>().allow_uninit);
+    }
+
+    #[test]
+    fn test_needs_drop_defaults_to_true() {
+        let type_infos = StaticTypeResolver::from(
+            serde_json::from_str::<BTreeMap<String, DynamicTypeInfo>>(
+                r#"{"u32": {"info": {"name": "u32", "size": 4, "align": 4}, "allow_uninit": true}}"#,
+            )
+            .unwrap(),
+        );
+
+        let type_info = type_infos.type_info::<u32>();
+        assert!(type_info.allow_uninit);
+        assert!(type_info.info.needs_drop);
+    }
+
+    #[test]
+    fn test_json_header() {
+        let mut type_infos = StaticTypeResolver::new();
+        type_infos.add_type_allow_uninit::<u32>();
+        type_infos.set_header(TypesHeader::current());
+
+        let json = type_infos.to_json_value().unwrap();
+        assert_eq!(
+            json["header"]["pointer_width"],
+            serde_json::json!(usize::BITS)
+        );
+
+        let deserialized: StaticTypeResolver = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.header(), Some(&TypesHeader::current()));
+        assert!(deserialized.type_info::<u32>().allow_uninit);
+
+        // Bare maps of types written by former versions
+        let deserialized: StaticTypeResolver = serde_json::from_str(
+            r#"{"u32": {"info": {"name": "u32", "size": 4, "align": 4}, "allow_uninit": true}}"#,
+        )
+        .unwrap();
+        assert_eq!(deserialized.header(), None);
+        assert!(deserialized.type_info::<u32>().allow_uninit);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut type_infos = StaticTypeResolver::new();
+        type_infos.add_type_allow_uninit::<u32>();
+        type_infos.add_type::<String>();
+
+        let mut other = StaticTypeResolver::new();
+        other.add_type_allow_uninit::<u32>();
+        other.add_type::<Vec<()>>();
+        other.set_header(TypesHeader::current());
+
+        type_infos.merge(other).unwrap();
+        assert_eq!(type_infos.header(), Some(&TypesHeader::current()));
+        assert_eq!(
+            type_infos.type_names().collect::<Vec<_>>(),
+            ["String", "Vec < () >", "u32"]
+        );
+
+        let mut conflicting = StaticTypeResolver::new();
+        conflicting.add_type::<u32>();
+        conflicting.add_type::<u64>();
+        let error = type_infos.merge(conflicting).unwrap_err();
+        let (ours, theirs) =
+            assert_matches!(error, MergeError::TypeConflict { ours, theirs } => (ours, theirs));
+        assert!(ours.allow_uninit);
+        assert!(!theirs.allow_uninit);
+        assert!(type_infos.try_type_info::<u64>().is_err());
+
+        let mut conflicting = StaticTypeResolver::new();
+        conflicting.set_header(TypesHeader {
+            target: "aarch64-unknown-linux-gnu".to_owned(),
+            ..TypesHeader::current()
+        });
+        let error = type_infos.merge(conflicting).unwrap_err();
+        assert_matches!(error, MergeError::HeaderConflict { .. });
+    }
+
+    #[test]
+    fn test_layered_type_resolver() {
+        struct Unknown;
+
+        let mut overrides = StaticTypeResolver::new();
+        overrides.add_dynamic_type(DynamicTypeInfo {
+            info: TypeInfo {
+                name: "Box<str>".to_owned(),
+                size: 42,
+                align: 2,
+                needs_drop: true,
+            },
+            allow_uninit: false,
+        });
+
+        let mut std_types = StaticTypeResolver::new();
+        std_types.add_std_types();
+
+        assert_eq!(
+            HostTypeResolver.try_dynamic_type_info("u32"),
+            Err(UnknownType::not_found("u32"))
+        );
+        assert!(std_types.try_type_info::<Unknown>().is_err());
+        assert_eq!(
+            std_types.try_dynamic_type_info("Unknown"),
+            Err(UnknownType::not_found("Unknown"))
+        );
+
+        let type_infos = overrides
+            .with_fallback(&std_types)
+            .with_fallback(HostTypeResolver);
+
+        let info = type_infos.dynamic_type_info("Box<str>");
+        assert_eq!(info.info.name, "Box < str >");
+        assert_eq!(info.info.size, 42);
+        assert_eq!(type_infos.type_info::<Box<str>>().info.size, 42);
+
+        assert!(type_infos.type_info::<u32>().allow_uninit);
+        assert!(type_infos.dynamic_type_info("u32").allow_uninit);
+
+        let info = type_infos.type_info::<Unknown>();
+        assert_eq!(info.info.size, 0);
+        assert!(!info.allow_uninit);
+
+        assert_eq!(
+            type_infos.try_dynamic_type_info("Option<Unknown>"),
+            Err(UnknownType {
+                type_name: "Option < Unknown >".to_owned(),
+                cause: UnknownTypeCause::Argument(Box::new(UnknownType::not_found("Unknown"))),
+            })
+        );
+        let result = std::panic::catch_unwind(|| type_infos.dynamic_type_info("Unknown"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_registered_types() {
+        #[derive(Clone, Copy)]
+        struct RegisteredCopyStruct;
+
+        struct RegisteredStruct(#[allow(dead_code)] String);
+
+        crate::register_types! {
+            fn register(RegisteredCopyStruct, RegisteredStruct);
+        }
+
+        let mut type_infos = StaticTypeResolver::default();
+
+        register(&mut type_infos);
+
+        assert!(type_infos.type_info::<RegisteredCopyStruct>().allow_uninit);
+        assert!(!type_infos.type_info::<RegisteredStruct>().allow_uninit);
+
+        let json = StaticTypeResolver::target_types_json(register).unwrap();
+        let target_types: StaticTypeResolver = serde_json::from_str(&json).unwrap();
+        assert_eq!(target_types.header(), Some(&TypesHeader::current()));
+        let type_names = target_types.type_names().collect::<Vec<_>>();
+        assert!(type_names.contains(&"u32"));
+        assert!(type_names.contains(&truc_type_name::<RegisteredStruct>().as_str()));
+    }
+}
@@ -0,0 +1,477 @@
+//! Resolution rules of generic and composite types.
+//!
+//! Layouts are derived from the layouts of a few reference types which must be known by the
+//! resolver, so that they remain correct when the types are computed for another target:
+//!
+//! * `usize` for thin pointers (`Box<T>`, `Arc<T>`, `Rc<T>`, `&T`) and fat pointers which are
+//!   twice as large (`Box<str>`, `Box<[T]>`, `Box<dyn Trait>`, ...)
+//! * `Vec<()>` for any vector
+//! * `String` for any string
+//!
+//! Arrays are computed from their element, tuples and options are computed from their fields the
+//! same way the Rust compiler lays them out.
+
+use std::collections::BTreeMap;
+
+use syn::{Expr, ExprLit, GenericArgument, Lit, PathArguments, Type, TypePath};
+
+use super::{DynamicTypeInfo, TypeInfo, UnknownType, UnknownTypeCause};
+
+/// Number of invalid bit patterns of a type that the compiler can use to encode the discriminant
+/// of an enum, e.g. `Option<&T>` is encoded with the null pointer.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Niches {
+    Exact(u128),
+    AtLeast(u128),
+}
+
+impl Niches {
+    fn count(self) -> u128 {
+        match self {
+            Self::Exact(count) | Self::AtLeast(count) => count,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Resolved {
+    info: DynamicTypeInfo,
+    niches: Niches,
+}
+
+/// Resolves `type_name`, which must be normalized, either by looking it up in `types` or by
+/// applying the rules.
+pub(super) fn resolve(
+    types: &BTreeMap<String, DynamicTypeInfo>,
+    type_name: &str,
+) -> Result<DynamicTypeInfo, UnknownType> {
+    if let Some(info) = types.get(type_name) {
+        return Ok(info.clone());
+    }
+    let syn_type =
+        syn::parse_str::<Type>(type_name).map_err(|_| UnknownType::not_found(type_name))?;
+    resolve_type(types, &syn_type).map(|resolved| resolved.info)
+}
+
+fn resolve_type(
+    types: &BTreeMap<String, DynamicTypeInfo>,
+    syn_type: &Type,
+) -> Result<Resolved, UnknownType> {
+    let name = quote!(#syn_type).to_string();
+    // Known types take precedence, but niches of composite types still come from the rules.
+    match (types.get(&name), apply_rules(types, syn_type, &name)) {
+        (Some(info), Ok(resolved)) => Ok(Resolved {
+            info: info.clone(),
+            niches: resolved.niches,
+        }),
+        (Some(info), Err(_)) => Ok(Resolved {
+            info: info.clone(),
+            niches: leaf_niches(&name),
+        }),
+        (None, resolved) => resolved,
+    }
+}
+
+/// Resolves a generic argument, an element or a field of the type `name`.
+fn resolve_argument(
+    types: &BTreeMap<String, DynamicTypeInfo>,
+    syn_type: &Type,
+    name: &str,
+) -> Result<Resolved, UnknownType> {
+    resolve_type(types, syn_type).map_err(|error| UnknownType {
+        type_name: name.to_owned(),
+        cause: UnknownTypeCause::Argument(Box::new(error)),
+    })
+}
+
+/// Looks up the reference type `reference` the layout of the type `name` is computed from.
+fn reference_type<'a>(
+    types: &'a BTreeMap<String, DynamicTypeInfo>,
+    reference: &str,
+    name: &str,
+) -> Result<&'a DynamicTypeInfo, UnknownType> {
+    types.get(reference).ok_or_else(|| {
+        if reference == name {
+            UnknownType::not_found(name)
+        } else {
+            UnknownType {
+                type_name: name.to_owned(),
+                cause: UnknownTypeCause::Reference(Box::new(UnknownType::not_found(reference))),
+            }
+        }
+    })
+}
+
+fn apply_rules(
+    types: &BTreeMap<String, DynamicTypeInfo>,
+    syn_type: &Type,
+    name: &str,
+) -> Result<Resolved, UnknownType> {
+    let layout_error = || UnknownType {
+        type_name: name.to_owned(),
+        cause: UnknownTypeCause::Layout,
+    };
+    let (size, align, needs_drop, allow_uninit, niches) = match syn_type {
+        Type::Paren(paren) => return resolve_type(types, &paren.elem),
+        Type::Group(group) => return resolve_type(types, &group.elem),
+        Type::Tuple(tuple) => {
+            let fields = tuple
+                .elems
+                .iter()
+                .map(|elem| resolve_argument(types, elem, name))
+                .collect::<Result<Vec<_>, _>>()?;
+            // Fields are reordered by decreasing alignment, there is no padding in between.
+            let align = fields
+                .iter()
+                .map(|field| field.info.info.align)
+                .max()
+                .unwrap_or(1);
+            let size = round_up(fields.iter().map(|field| field.info.info.size).sum(), align);
+            let niches = fields.iter().map(|field| field.niches).fold(
+                Niches::Exact(0),
+                |acc, niches| match (acc, niches) {
+                    (Niches::Exact(a), Niches::Exact(b)) => Niches::Exact(a.max(b)),
+                    (a, b) => Niches::AtLeast(a.count().max(b.count())),
+                },
+            );
+            (
+                size,
+                align,
+                fields.iter().any(|field| field.info.info.needs_drop),
+                fields.iter().all(|field| field.info.allow_uninit),
+                niches,
+            )
+        }
+        Type::Array(array) => {
+            let len = match &array.len {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(len), ..
+                }) => len.base10_parse::<usize>().map_err(|_| layout_error())?,
+                _ => return Err(layout_error()),
+            };
+            let elem = resolve_argument(types, &array.elem, name)?;
+            (
+                elem.info
+                    .info
+                    .size
+                    .checked_mul(len)
+                    .ok_or_else(layout_error)?,
+                elem.info.info.align,
+                len > 0 && elem.info.info.needs_drop,
+                elem.info.allow_uninit,
+                if len > 0 {
+                    elem.niches
+                } else {
+                    Niches::Exact(0)
+                },
+            )
+        }
+        Type::Reference(reference) => {
+            let (size, align) = pointer_layout(types, &reference.elem, name)?;
+            (
+                size,
+                align,
+                false,
+                reference.mutability.is_none(),
+                Niches::AtLeast(1),
+            )
+        }
+        Type::Path(path) => {
+            let (family, args) = std_family(path).ok_or_else(|| UnknownType::not_found(name))?;
+            match (family.as_str(), args.as_slice()) {
+                ("String", []) => {
+                    let string = reference_type(types, "String", name)?;
+                    (
+                        string.info.size,
+                        string.info.align,
+                        true,
+                        false,
+                        Niches::AtLeast(1),
+                    )
+                }
+                ("Vec", [_]) => {
+                    let vec = reference_type(types, "Vec < () >", name)?;
+                    (
+                        vec.info.size,
+                        vec.info.align,
+                        true,
+                        false,
+                        Niches::AtLeast(1),
+                    )
+                }
+                ("Box" | "Arc" | "Rc", [pointee]) => {
+                    let (size, align) = pointer_layout(types, pointee, name)?;
+                    (size, align, true, false, Niches::AtLeast(1))
+                }
+                ("Option", [some]) => {
+                    let some = resolve_argument(types, some, name)?;
+                    let (size, niches) = match some.niches {
+                        Niches::Exact(0) => (
+                            // The discriminant is a byte placed after the value.
+                            round_up(some.info.info.size + 1, some.info.info.align),
+                            Niches::AtLeast(u128::from(u8::MAX) - 1),
+                        ),
+                        Niches::AtLeast(0) => return Err(layout_error()),
+                        Niches::Exact(count) => (some.info.info.size, Niches::Exact(count - 1)),
+                        Niches::AtLeast(count) => (some.info.info.size, Niches::AtLeast(count - 1)),
+                    };
+                    (
+                        size,
+                        some.info.info.align,
+                        some.info.info.needs_drop,
+                        some.info.allow_uninit,
+                        niches,
+                    )
+                }
+                _ => return Err(UnknownType::not_found(name)),
+            }
+        }
+        _ => return Err(UnknownType::not_found(name)),
+    };
+    Ok(Resolved {
+        info: DynamicTypeInfo {
+            info: TypeInfo {
+                name: name.to_owned(),
+                size,
+                align,
+                needs_drop,
+            },
+            allow_uninit,
+        },
+        niches,
+    })
+}
+
+/// Niches of types known by the resolver, unknown types may have niches or not.
+fn leaf_niches(type_name: &str) -> Niches {
+    match type_name {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+        | "isize" | "f32" | "f64" | "()" => Niches::Exact(0),
+        "bool" => Niches::Exact(u128::from(u8::MAX) - 1),
+        "char" => Niches::Exact(u128::from(u32::MAX) - u128::from(u32::from(char::MAX))),
+        "String" | "Box < str >" | "Vec < () >" => Niches::AtLeast(1),
+        _ => Niches::AtLeast(0),
+    }
+}
+
+/// Gives the name and the type arguments of a standard generic type, which may be qualified by
+/// its crate and module or not.
+fn std_family(path: &TypePath) -> Option<(String, Vec<&Type>)> {
+    if path.qself.is_some() {
+        return None;
+    }
+    let segments = &path.path.segments;
+    let first = segments.first()?;
+    if segments.len() > 1 && !["std", "alloc", "core"].iter().any(|c| first.ident == c) {
+        return None;
+    }
+    let last = segments.last()?;
+    let args = match &last.arguments {
+        PathArguments::None => Vec::new(),
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .map(|arg| match arg {
+                GenericArgument::Type(arg) => Some(arg),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?,
+        PathArguments::Parenthesized(_) => return None,
+    };
+    Some((last.ident.to_string(), args))
+}
+
+/// Gives the layout of a pointer to `pointee` in the type `name`, fat pointers being twice as large
+/// as thin pointers.
+fn pointer_layout(
+    types: &BTreeMap<String, DynamicTypeInfo>,
+    pointee: &Type,
+    name: &str,
+) -> Result<(usize, usize), UnknownType> {
+    let usize_info = &reference_type(types, "usize", name)?.info;
+    let sized = match pointee {
+        Type::Slice(_) | Type::TraitObject(_) => false,
+        Type::Path(path) => !path.path.is_ident("str"),
+        _ => true,
+    };
+    Ok(if sized {
+        (usize_info.size, usize_info.align)
+    } else {
+        (usize_info.size * 2, usize_info.align)
+    })
+}
+
+fn round_up(size: usize, align: usize) -> usize {
+    (size + align - 1) / align * align
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::{rc::Rc, sync::Arc};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::record::{
+        type_name::{truc_dynamic_type_name, truc_type_name},
+        type_resolver::StaticTypeResolver,
+    };
+
+    fn reference_types() -> BTreeMap<String, DynamicTypeInfo> {
+        let mut resolver = StaticTypeResolver::new();
+        resolver.add_std_types();
+        serde_json::from_value(resolver.to_json_value().unwrap()["types"].take()).unwrap()
+    }
+
+    fn assert_host_layout<T>(types: &BTreeMap<String, DynamicTypeInfo>) {
+        let type_name = truc_type_name::<T>();
+        let info = resolve(types, &type_name)
+            .unwrap_or_else(|error| panic!("Could not resolve type {}", error));
+        assert_eq!(
+            info.info,
+            TypeInfo {
+                name: type_name,
+                size: std::mem::size_of::<T>(),
+                align: std::mem::align_of::<T>(),
+                needs_drop: std::mem::needs_drop::<T>(),
+            }
+        );
+    }
+
+    #[allow(dead_code)]
+    struct Unknown(u16, bool);
+
+    #[test]
+    fn test_pointers() {
+        let types = reference_types();
+        assert_host_layout::<Vec<Unknown>>(&types);
+        assert_host_layout::<Box<Unknown>>(&types);
+        assert_host_layout::<Box<[Unknown]>>(&types);
+        assert_host_layout::<Box<dyn std::any::Any>>(&types);
+        assert_host_layout::<Arc<Unknown>>(&types);
+        assert_host_layout::<Rc<str>>(&types);
+        assert_host_layout::<&'static Unknown>(&types);
+        assert_host_layout::<&'static [u8]>(&types);
+    }
+
+    #[test]
+    fn test_arrays() {
+        let types = reference_types();
+        assert_host_layout::<[u32; 42]>(&types);
+        assert_host_layout::<[String; 0]>(&types);
+        assert_host_layout::<[[Vec<Unknown>; 3]; 12]>(&types);
+        assert_host_layout::<[(u8, u64); 11]>(&types);
+    }
+
+    #[test]
+    fn test_tuples() {
+        let types = reference_types();
+        assert_host_layout::<()>(&types);
+        assert_host_layout::<(u8,)>(&types);
+        assert_host_layout::<(u8, u64, u16)>(&types);
+        assert_host_layout::<(u8, (u32, u8), String)>(&types);
+        assert_host_layout::<(bool, [u16; 3], char)>(&types);
+    }
+
+    #[test]
+    fn test_options() {
+        let types = reference_types();
+        assert_host_layout::<Option<&'static Unknown>>(&types);
+        assert_host_layout::<Option<Box<Unknown>>>(&types);
+        assert_host_layout::<Option<Box<str>>>(&types);
+        assert_host_layout::<Option<Vec<Unknown>>>(&types);
+        assert_host_layout::<Option<(u32, u8)>>(&types);
+        assert_host_layout::<Option<Option<u64>>>(&types);
+        assert_host_layout::<Option<Option<bool>>>(&types);
+        assert_host_layout::<Option<(u16, char)>>(&types);
+        assert_host_layout::<Option<[bool; 4]>>(&types);
+        assert_host_layout::<Option<()>>(&types);
+    }
+
+    #[test]
+    fn test_copy() {
+        let types = reference_types();
+        for (type_name, allow_uninit) in [
+            ("(u32, char)", true),
+            ("(u32, String)", false),
+            ("[u64; 20]", true),
+            ("Option<[(u8, bool); 20]>", true),
+            ("&'static str", true),
+            ("&'static mut str", false),
+            ("Arc<u32>", false),
+        ] {
+            let info = resolve(&types, &truc_dynamic_type_name(type_name)).unwrap();
+            assert_eq!(info.allow_uninit, allow_uninit, "{}", type_name);
+        }
+    }
+
+    #[test]
+    fn test_unresolved() {
+        let types = reference_types();
+        let unknown = || Box::new(UnknownType::not_found("Unknown"));
+        for (type_name, cause) in [
+            ("Unknown", UnknownTypeCause::NotFound),
+            ("Option<Unknown>", UnknownTypeCause::Argument(unknown())),
+            ("[Unknown; 2]", UnknownTypeCause::Argument(unknown())),
+            ("(u32, Unknown)", UnknownTypeCause::Argument(unknown())),
+            ("my_crate::Vec<u32>", UnknownTypeCause::NotFound),
+            ("[u32; N]", UnknownTypeCause::Layout),
+            ("<T as Trait>::Vec", UnknownTypeCause::NotFound),
+        ] {
+            let type_name = truc_dynamic_type_name(type_name);
+            assert_eq!(
+                resolve(&types, &type_name),
+                Err(UnknownType { type_name, cause })
+            );
+        }
+    }
+
+    #[test]
+    fn test_unresolved_nested_argument() {
+        let types = reference_types();
+        let type_name = truc_dynamic_type_name("[Option<Unknown>; 2]");
+        assert_eq!(
+            resolve(&types, &type_name),
+            Err(UnknownType {
+                type_name,
+                cause: UnknownTypeCause::Argument(Box::new(UnknownType {
+                    type_name: truc_dynamic_type_name("Option<Unknown>"),
+                    cause: UnknownTypeCause::Argument(Box::new(UnknownType::not_found("Unknown"))),
+                })),
+            })
+        );
+    }
+
+    #[test]
+    fn test_unresolved_reference_type() {
+        let mut types = reference_types();
+        types.remove("usize");
+        assert_eq!(
+            resolve(&types, &truc_dynamic_type_name("Box<u32>")),
+            Err(UnknownType {
+                type_name: "Box < u32 >".to_owned(),
+                cause: UnknownTypeCause::Reference(Box::new(UnknownType::not_found("usize"))),
+            })
+        );
+        assert_eq!(
+            resolve(&types, "usize"),
+            Err(UnknownType::not_found("usize"))
+        );
+    }
+
+    #[test]
+    fn test_unresolved_layout() {
+        let mut types = reference_types();
+        let mut known = types["u32"].clone();
+        known.info.name = "Known".to_owned();
+        types.insert("Known".to_owned(), known);
+        assert_eq!(
+            resolve(&types, &truc_dynamic_type_name("Option<Known>")),
+            Err(UnknownType {
+                type_name: "Option < Known >".to_owned(),
+                cause: UnknownTypeCause::Layout,
+            })
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Trait-based probing of type properties which cannot be queried in generic code.
+//!
+//! Without specialization, there is no way to know whether a generic type `T` is `Copy`. However,
+//! when the type is concretely named, method resolution can be used to pick an implementation
+//! depending on the traits implemented by the type: the method of [CopyProbe] is only available
+//! when the type is `Copy`, otherwise method resolution falls back to the method of
+//! [NotCopyProbe] which requires an additional auto-reference.
+//!
+//! This is why probing is done with macros, see [is_copy].
+
+#![no_std]
+#![cfg_attr(coverage_nightly, feature(coverage_attribute))]
+
+use core::marker::PhantomData;
+
+/// Zero sized value used to probe properties of `T`.
+pub struct Probe<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> Probe<T> {
+    /// Creates a new probe.
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: ?Sized> Default for Probe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Probing trait selected when the type is `Copy`.
+pub trait CopyProbe {
+    /// Indicates that the probed type is `Copy`.
+    fn is_copy(&self) -> bool {
+        true
+    }
+}
+
+impl<T: Copy> CopyProbe for Probe<T> {}
+
+/// Probing trait selected when the type is not `Copy`.
+pub trait NotCopyProbe {
+    /// Indicates that the probed type is not `Copy`.
+    fn is_copy(&self) -> bool {
+        false
+    }
+}
+
+impl<T: ?Sized> NotCopyProbe for &Probe<T> {}
+
+/// Evaluates to `true` if the given type is `Copy`, `false` otherwise.
+///
+/// The type must be concrete, in generic code the probe always evaluates to `false`.
+///
+/// ```rust
+/// assert!(truc_probe::is_copy!(u32));
+/// assert!(truc_probe::is_copy!([Option<char>; 4]));
+/// assert!(!truc_probe::is_copy!(String));
+/// ```
+#[macro_export]
+macro_rules! is_copy {
+    ($type:ty) => {{
+        #[allow(unused_imports)]
+        use $crate::{CopyProbe as _, NotCopyProbe as _};
+        (&$crate::Probe::<$type>::new()).is_copy()
+    }};
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    extern crate std;
+
+    use std::{boxed::Box, string::String, vec::Vec};
+
+    #[derive(Clone, Copy)]
+    struct CopyStruct;
+
+    #[derive(Clone)]
+    struct CloneStruct;
+
+    #[test]
+    fn test_is_copy() {
+        assert!(is_copy!(u8));
+        assert!(is_copy!(bool));
+        assert!(is_copy!(Option<u64>));
+        assert!(is_copy!([char; 3]));
+        assert!(is_copy!(&'static str));
+        assert!(is_copy!(CopyStruct));
+
+        assert!(!is_copy!(String));
+        assert!(!is_copy!(Box<str>));
+        assert!(!is_copy!(Vec<()>));
+        assert!(!is_copy!(Option<String>));
+        assert!(!is_copy!(CloneStruct));
+        assert!(!is_copy!(str));
+    }
+
+    #[test]
+    fn test_generic_is_not_copy() {
+        fn probe<T>() -> bool {
+            is_copy!(T)
+        }
+
+        assert!(!probe::<u8>());
+    }
+}
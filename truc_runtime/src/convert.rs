@@ -1,5 +1,8 @@
-use std::{
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::{
     any::type_name,
+    marker::PhantomData,
     mem::{ManuallyDrop, MaybeUninit},
 };
 
@@ -19,9 +22,13 @@ pub enum VecElementConversionResult<T> {
 ///
 /// Note: the 2 required conditions are checked at runtime. However it is reasonably expected that
 /// those runtime checks are optimized statically by the compiler: NOOP or pure panic.
+///
+/// Each element is converted into at most one element. Use [convert_vec_in_place_flat_map] to
+/// emit several elements for a single input element into the slots freed by abandonned ones.
+#[cfg(feature = "alloc")]
 pub fn convert_vec_in_place<T, U, C>(input: Vec<T>, convert: C) -> Vec<U>
 where
-    C: Fn(T, Option<&mut U>) -> VecElementConversionResult<U> + std::panic::RefUnwindSafe,
+    C: Fn(T, Option<&mut U>) -> VecElementConversionResult<U> + core::panic::RefUnwindSafe,
 {
     try_convert_vec_in_place(input, |t, u| -> Result<_, ()> { Ok(convert(t, u)) }).unwrap()
 }
@@ -34,11 +41,558 @@ where
 ///
 /// Note: the 2 required conditions are checked at runtime. However it is reasonably expected that
 /// those runtime checks are optimized statically by the compiler: NOOP or pure panic.
+///
+/// Each element is converted into at most one element. Use [try_convert_vec_in_place_flat_map] to
+/// emit several elements for a single input element into the slots freed by abandonned ones.
+#[cfg(feature = "alloc")]
 pub fn try_convert_vec_in_place<T, U, C, E>(input: Vec<T>, convert: C) -> Result<Vec<U>, E>
 where
     C: Fn(T, Option<&mut U>) -> Result<VecElementConversionResult<U>, E>
-        + std::panic::RefUnwindSafe,
+        + core::panic::RefUnwindSafe,
+{
+    try_convert_vec_in_place_indexed(input, |_, t, u| convert(t, u))
+}
+
+/// Converts a vector of `T` to a vector of `U` with a converter allowed to mutate its state, see
+/// [convert_vec_in_place].
+///
+/// The converter does not need to be `RefUnwindSafe`: panics are not caught, the values are
+/// dropped while unwinding, so the state of the converter is never observed after a panic.
+///
+/// Use [convert_vec_in_place_flat_map] to emit several elements for a single input element.
+#[cfg(feature = "alloc")]
+pub fn convert_vec_in_place_mut<T, U, C>(input: Vec<T>, mut convert: C) -> Vec<U>
+where
+    C: FnMut(T, Option<&mut U>) -> VecElementConversionResult<U>,
+{
+    try_convert_vec_in_place_indexed(input, |_, t, u| -> Result<_, ()> { Ok(convert(t, u)) })
+        .unwrap()
+}
+
+/// Converts a vector of `T` to a vector of `U` with a converter allowed to mutate its state, see
+/// [try_convert_vec_in_place].
+///
+/// The converter does not need to be `RefUnwindSafe`: panics are not caught, the values are
+/// dropped while unwinding, so the state of the converter is never observed after a panic.
+///
+/// Use [try_convert_vec_in_place_flat_map] to emit several elements for a single input element.
+#[cfg(feature = "alloc")]
+pub fn try_convert_vec_in_place_mut<T, U, C, E>(input: Vec<T>, mut convert: C) -> Result<Vec<U>, E>
+where
+    C: FnMut(T, Option<&mut U>) -> Result<VecElementConversionResult<U>, E>,
+{
+    try_convert_vec_in_place_indexed(input, |_, t, u| convert(t, u))
+}
+
+/// Converts a vector of `T` to a vector of `U` with a converter receiving the index of each
+/// element in the input vector, see [convert_vec_in_place].
+///
+/// The converter is allowed to mutate its state.
+///
+/// Use [convert_vec_in_place_flat_map] to emit several elements for a single input element.
+#[cfg(feature = "alloc")]
+pub fn convert_vec_in_place_indexed<T, U, C>(input: Vec<T>, mut convert: C) -> Vec<U>
+where
+    C: FnMut(usize, T, Option<&mut U>) -> VecElementConversionResult<U>,
+{
+    try_convert_vec_in_place_indexed(input, |i, t, u| -> Result<_, ()> { Ok(convert(i, t, u)) })
+        .unwrap()
+}
+
+/// Converts a vector of `T` to a vector of `U` with a converter receiving the index of each
+/// element in the input vector, see [try_convert_vec_in_place].
+///
+/// The converter is allowed to mutate its state.
+///
+/// Use [try_convert_vec_in_place_flat_map] to emit several elements for a single input element.
+#[cfg(feature = "alloc")]
+pub fn try_convert_vec_in_place_indexed<T, U, C, E>(
+    input: Vec<T>,
+    mut convert: C,
+) -> Result<Vec<U>, E>
+where
+    C: FnMut(usize, T, Option<&mut U>) -> Result<VecElementConversionResult<U>, E>,
+{
+    assert_same_layout::<T, U>();
+
+    // Let's take control, we know what we're doing
+    let (mut buffer, ptr, len) = take_elements(input);
+    let slice = unsafe { core::slice::from_raw_parts_mut(ptr, len) };
+
+    let conversion = convert_slice_in_place(slice, &mut convert);
+
+    match conversion.outcome {
+        Ok(()) => {
+            unsafe {
+                buffer.set_len(conversion.first_moved);
+            }
+            Ok(unsafe { core::mem::transmute::<Vec<T>, Vec<U>>(buffer) })
+        }
+        Err(err) => {
+            clean_on_error::<T, U>(slice, conversion.first_moved, conversion.first_ttt);
+            Err(err)
+        }
+    }
+}
+
+/// Converts a vector of `T` to a vector of `U` with a converter emitting any number of elements
+/// for each input element, see [convert_vec_in_place].
+///
+/// Every input element frees a slot in the buffer, so the converter may emit several elements as
+/// long as enough elements have been abandonned before. It panics otherwise.
+///
+/// The converter is allowed to mutate its state.
+///
+/// ```rust
+/// # use truc_runtime::convert::convert_vec_in_place_flat_map;
+/// let output = convert_vec_in_place_flat_map::<u32, i32, _, _>(vec![0, 1, 2, 3], |value| {
+///     if value % 2 == 0 {
+///         // Abandonned, the slot is reused by the next element
+///         vec![]
+///     } else {
+///         vec![value as i32, -(value as i32)]
+///     }
+/// });
+/// assert_eq!(output, vec![1, -1, 3, -3]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn convert_vec_in_place_flat_map<T, U, I, C>(input: Vec<T>, mut convert: C) -> Vec<U>
+where
+    I: IntoIterator<Item = U>,
+    C: FnMut(T) -> I,
+{
+    try_convert_vec_in_place_flat_map(input, |t| -> Result<_, ()> { Ok(convert(t)) }).unwrap()
+}
+
+/// Converts a vector of `T` to a vector of `U` with a converter emitting any number of elements
+/// for each input element, see [try_convert_vec_in_place].
+///
+/// Every input element frees a slot in the buffer, so the converter may emit several elements as
+/// long as enough elements have been abandonned before. It panics otherwise.
+///
+/// The converter is allowed to mutate its state.
+#[cfg(feature = "alloc")]
+pub fn try_convert_vec_in_place_flat_map<T, U, I, C, E>(
+    input: Vec<T>,
+    mut convert: C,
+) -> Result<Vec<U>, E>
+where
+    I: IntoIterator<Item = U>,
+    C: FnMut(T) -> Result<I, E>,
+{
+    let mut converter = VecInPlaceConverter::<T, U>::new(input);
+    while let Some(ttt) = converter.next() {
+        for uuu in convert(ttt)? {
+            converter.push(uuu);
+        }
+    }
+    Ok(converter.finish())
+}
+
+/// Converts a ring buffer of `T` to a ring buffer of `U`, see [convert_vec_in_place].
+///
+/// The ring buffer is made contiguous first. The buffer is reused since Rust 1.67, but the
+/// conversions between `VecDeque` and `Vec` may reallocate with older toolchains.
+#[cfg(feature = "alloc")]
+pub fn convert_vec_deque_in_place<T, U, C>(input: VecDeque<T>, convert: C) -> VecDeque<U>
+where
+    C: Fn(T, Option<&mut U>) -> VecElementConversionResult<U> + core::panic::RefUnwindSafe,
+{
+    VecDeque::from(convert_vec_in_place(Vec::from(input), convert))
+}
+
+/// Converts a ring buffer of `T` to a ring buffer of `U`, see [try_convert_vec_in_place].
+///
+/// The ring buffer is made contiguous first. The buffer is reused since Rust 1.67, but the
+/// conversions between `VecDeque` and `Vec` may reallocate with older toolchains.
+#[cfg(feature = "alloc")]
+pub fn try_convert_vec_deque_in_place<T, U, C, E>(
+    input: VecDeque<T>,
+    convert: C,
+) -> Result<VecDeque<U>, E>
+where
+    C: Fn(T, Option<&mut U>) -> Result<VecElementConversionResult<U>, E>
+        + core::panic::RefUnwindSafe,
+{
+    try_convert_vec_in_place(Vec::from(input), convert).map(VecDeque::from)
+}
+
+/// Converts a boxed slice of `T` to a boxed slice of `U`, reallocating if any element is
+/// abandonned, see [convert_vec_in_place].
+///
+/// A boxed slice cannot keep spare capacity so the buffer is shrunk, which reallocates, as soon as
+/// one element is abandonned. Convert with [convert_vec_in_place] instead to keep the original
+/// allocation in all cases.
+#[cfg(feature = "alloc")]
+pub fn convert_boxed_slice_in_place<T, U, C>(input: Box<[T]>, convert: C) -> Box<[U]>
+where
+    C: Fn(T, Option<&mut U>) -> VecElementConversionResult<U> + core::panic::RefUnwindSafe,
+{
+    convert_vec_in_place(input.into_vec(), convert).into_boxed_slice()
+}
+
+/// Converts a boxed slice of `T` to a boxed slice of `U`, reallocating if any element is
+/// abandonned, see [try_convert_vec_in_place].
+///
+/// A boxed slice cannot keep spare capacity so the buffer is shrunk, which reallocates, as soon as
+/// one element is abandonned. Convert with [try_convert_vec_in_place] instead to keep the original
+/// allocation in all cases.
+#[cfg(feature = "alloc")]
+pub fn try_convert_boxed_slice_in_place<T, U, C, E>(
+    input: Box<[T]>,
+    convert: C,
+) -> Result<Box<[U]>, E>
+where
+    C: Fn(T, Option<&mut U>) -> Result<VecElementConversionResult<U>, E>
+        + core::panic::RefUnwindSafe,
+{
+    try_convert_vec_in_place(input.into_vec(), convert).map(Vec::into_boxed_slice)
+}
+
+/// Converts an array of `T` to an array of `U` where `T` and `U` have the same size in memory and
+/// the same alignment rule according to the Rust compiler.
+///
+/// Since the size of the array cannot change, elements cannot be abandonned.
+///
+/// If the provided converter panics then your memory is safe: no invalid access is performed,
+/// values that need to be dropped are dropped.
+///
+/// Note: the 2 required conditions are checked at runtime. However it is reasonably expected that
+/// those runtime checks are optimized statically by the compiler: NOOP or pure panic.
+pub fn convert_array_in_place<T, U, C, const N: usize>(input: [T; N], convert: C) -> [U; N]
+where
+    C: Fn(T, Option<&mut U>) -> U + core::panic::RefUnwindSafe,
+{
+    try_convert_array_in_place(input, |t, u| -> Result<_, ()> { Ok(convert(t, u)) }).unwrap()
+}
+
+/// Converts an array of `T` to an array of `U` where `T` and `U` have the same size in memory and
+/// the same alignment rule according to the Rust compiler.
+///
+/// Since the size of the array cannot change, elements cannot be abandonned.
+///
+/// If the provided converter panics then your memory is safe: no invalid access is performed,
+/// values that need to be dropped are dropped.
+///
+/// Note: the 2 required conditions are checked at runtime. However it is reasonably expected that
+/// those runtime checks are optimized statically by the compiler: NOOP or pure panic.
+pub fn try_convert_array_in_place<T, U, C, E, const N: usize>(
+    input: [T; N],
+    convert: C,
+) -> Result<[U; N], E>
+where
+    C: Fn(T, Option<&mut U>) -> Result<U, E> + core::panic::RefUnwindSafe,
+{
+    assert_same_layout::<T, U>();
+
+    // Let's take control, we know what we're doing
+    let mut manually_drop = ManuallyDrop::new(input);
+
+    let conversion = convert_slice_in_place(&mut manually_drop[..], &mut |_, t, u| {
+        convert(t, u).map(VecElementConversionResult::Converted)
+    });
+
+    match conversion.outcome {
+        Ok(()) => {
+            debug_assert_eq!(conversion.first_moved, N);
+            Ok(unsafe { core::ptr::read((&*manually_drop as *const [T; N]).cast::<[U; N]>()) })
+        }
+        Err(err) => {
+            clean_on_error::<T, U>(
+                &mut manually_drop[..],
+                conversion.first_moved,
+                conversion.first_ttt,
+            );
+            Err(err)
+        }
+    }
+}
+
+/// Converts a vector of `T` to a vector of `U` in parallel, see [convert_vec_in_place].
+///
+/// The vector is split into chunks which are converted on the `rayon` thread pool, then the
+/// converted elements are compacted at the beginning of the buffer. Unlike the sequential
+/// converters, the converter is not given the preceding converted element since it would depend on
+/// how the vector is split.
+///
+/// If the provided converter panics then your memory is safe: no invalid access is performed,
+/// values that need to be dropped are dropped.
+#[cfg(feature = "rayon")]
+pub fn par_convert_vec_in_place<T, U, C>(input: Vec<T>, convert: C) -> Vec<U>
+where
+    T: Send,
+    U: Send,
+    C: Fn(T) -> VecElementConversionResult<U> + Sync + core::panic::RefUnwindSafe,
+{
+    try_par_convert_vec_in_place(input, |t| -> Result<_, ()> { Ok(convert(t)) }).unwrap()
+}
+
+/// Converts a vector of `T` to a vector of `U` in parallel, see [try_convert_vec_in_place].
+///
+/// The vector is split into chunks which are converted on the `rayon` thread pool, then the
+/// converted elements are compacted at the beginning of the buffer. Unlike the sequential
+/// converters, the converter is not given the preceding converted element since it would depend on
+/// how the vector is split.
+///
+/// If the provided converter panics then your memory is safe: no invalid access is performed,
+/// values that need to be dropped are dropped. If several chunks fail, the error (or panic) of the
+/// first failing chunk is reported.
+#[cfg(feature = "rayon")]
+pub fn try_par_convert_vec_in_place<T, U, C, E>(input: Vec<T>, convert: C) -> Result<Vec<U>, E>
+where
+    T: Send,
+    U: Send,
+    E: Send,
+    C: Fn(T) -> Result<VecElementConversionResult<U>, E> + Sync + core::panic::RefUnwindSafe,
 {
+    use rayon::prelude::*;
+
+    assert_same_layout::<T, U>();
+
+    // Let's take control, we know what we're doing
+    let (mut buffer, ptr, len) = take_elements(input);
+    let slice = unsafe { core::slice::from_raw_parts_mut(ptr, len) };
+
+    let chunk_size = core::cmp::max(
+        1,
+        (slice.len() + rayon::current_num_threads() - 1) / rayon::current_num_threads(),
+    );
+
+    // Panics are caught so that the chunks converted by other threads are dropped as well
+    let conversions = slice
+        .par_chunks_mut(chunk_size)
+        .map(|chunk| {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                convert_slice_in_place(chunk, &mut |_, t, _| convert(t))
+            }))
+        })
+        .collect::<Vec<_>>();
+
+    if conversions.iter().all(|conversion| {
+        matches!(
+            conversion,
+            Ok(SliceConversion {
+                outcome: Ok(()),
+                ..
+            })
+        )
+    }) {
+        // Move the converted elements of each chunk right after the ones of the previous chunks
+        let ptr = slice.as_mut_ptr();
+        let mut len = 0;
+        for (index, conversion) in conversions.iter().enumerate() {
+            let first_moved = conversion
+                .as_ref()
+                .map_or(0, |conversion| conversion.first_moved);
+            let chunk_start = index * chunk_size;
+            if chunk_start != len {
+                unsafe {
+                    core::ptr::copy(ptr.add(chunk_start), ptr.add(len), first_moved);
+                }
+            }
+            len += first_moved;
+        }
+        unsafe {
+            buffer.set_len(len);
+        }
+        return Ok(unsafe { core::mem::transmute::<Vec<T>, Vec<U>>(buffer) });
+    }
+
+    let mut failure = None;
+    for (chunk, conversion) in slice.chunks_mut(chunk_size).zip(conversions) {
+        match conversion {
+            Ok(conversion) => {
+                clean_on_error::<T, U>(chunk, conversion.first_moved, conversion.first_ttt);
+                if let Err(err) = conversion.outcome {
+                    failure.get_or_insert(Ok(err));
+                }
+            }
+            Err(panic) => {
+                // The chunk has already been cleaned while unwinding
+                failure.get_or_insert(Err(panic));
+            }
+        }
+    }
+    match failure {
+        Some(Ok(err)) => Err(err),
+        Some(Err(panic)) => std::panic::resume_unwind(panic),
+        None => unreachable!("at least one chunk conversion failed"),
+    }
+}
+
+/// Cursor converting a vector of `T` to a vector of `U` in place, one element at a time, where `T`
+/// and `U` have the same size in memory and the same alignment rule according to the Rust
+/// compiler.
+///
+/// Elements of type `T` are pulled with [`Iterator::next`] and converted elements are pushed with
+/// [`push`](Self::push), the buffer is finally reinterpreted as a vector of `U` by
+/// [`finish`](Self::finish). Every pulled element frees a slot in the buffer, so not pushing
+/// anything for an element abandons it.
+///
+/// Slots freed by abandoned elements remain available, they can be used to push more than one
+/// element for a single pulled element, see [`available`](Self::available).
+///
+/// If the converter is dropped before being finished then your memory is safe: no invalid access
+/// is performed, values that need to be dropped are dropped.
+///
+/// ```rust
+/// # use truc_runtime::convert::VecInPlaceConverter;
+/// let mut converter = VecInPlaceConverter::<u32, i32>::new(vec![1, 2, 3]);
+/// while let Some(value) = converter.next() {
+///     if value != 2 {
+///         converter.push(-(value as i32));
+///     }
+/// }
+/// assert_eq!(converter.finish(), vec![-1, -3]);
+/// ```
+#[cfg(feature = "alloc")]
+pub struct VecInPlaceConverter<T, U> {
+    // The elements are managed by the converter, the vector only owns the buffer
+    buffer: Vec<T>,
+    // The buffer is divided into 3 areas:
+    //
+    // - 0..first_moved: elements of type U
+    // - first_moved..first_ttt: moved elements
+    // - first_ttt..len: elements of type T
+    len: usize,
+    first_moved: usize,
+    first_ttt: usize,
+    _phantom: PhantomData<U>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, U> VecInPlaceConverter<T, U> {
+    /// Takes control of the vector to convert.
+    ///
+    /// Note: the 2 required conditions are checked at runtime. However it is reasonably expected
+    /// that those runtime checks are optimized statically by the compiler: NOOP or pure panic.
+    pub fn new(input: Vec<T>) -> Self {
+        assert_same_layout::<T, U>();
+
+        let mut buffer = input;
+        let len = buffer.len();
+        unsafe {
+            buffer.set_len(0);
+        }
+        Self {
+            buffer,
+            len,
+            first_moved: 0,
+            first_ttt: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Gets the number of elements of type `T` which have not been pulled yet.
+    pub fn remaining(&self) -> usize {
+        self.len - self.first_ttt
+    }
+
+    /// Gets the number of elements of type `U` which can be pushed right now.
+    pub fn available(&self) -> usize {
+        self.first_ttt - self.first_moved
+    }
+
+    /// Pushes a converted element.
+    ///
+    /// It panics if there is no available slot, see [`try_push`](Self::try_push).
+    pub fn push(&mut self, uuu: U) {
+        if self.try_push(uuu).is_err() {
+            panic!(
+                "No slot available to push {}, pull more {} first",
+                type_name::<U>(),
+                type_name::<T>()
+            );
+        }
+    }
+
+    /// Pushes a converted element if there is an available slot, otherwise gives it back.
+    pub fn try_push(&mut self, uuu: U) -> Result<(), U> {
+        if self.first_moved < self.first_ttt {
+            unsafe {
+                core::ptr::write(self.buffer.as_mut_ptr().add(self.first_moved).cast(), uuu);
+            }
+            // The element is now converted
+            self.first_moved += 1;
+            Ok(())
+        } else {
+            Err(uuu)
+        }
+    }
+
+    /// Gets a mutable reference on the last converted element if it exists.
+    pub fn last_mut(&mut self) -> Option<&mut U> {
+        if self.first_moved > 0 {
+            Some(unsafe { &mut *self.buffer.as_mut_ptr().add(self.first_moved - 1).cast() })
+        } else {
+            None
+        }
+    }
+
+    /// Drops the elements which have not been pulled and gives back the converted ones.
+    pub fn finish(mut self) -> Vec<U> {
+        for ttt in &mut self {
+            drop(ttt);
+        }
+
+        let mut buffer = core::mem::take(&mut self.buffer);
+        let len = self.first_moved;
+        self.len = 0;
+        self.first_moved = 0;
+        self.first_ttt = 0;
+
+        unsafe {
+            buffer.set_len(len);
+            core::mem::transmute::<Vec<T>, Vec<U>>(buffer)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, U> Iterator for VecInPlaceConverter<T, U> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.first_ttt < self.len {
+            let ttt = unsafe { core::ptr::read(self.buffer.as_ptr().add(self.first_ttt)) };
+            // The element in the buffer is now moved
+            self.first_ttt += 1;
+            Some(ttt)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, U> Drop for VecInPlaceConverter<T, U> {
+    fn drop(&mut self) {
+        let ptr = self.buffer.as_mut_ptr();
+        let (first_moved, first_ttt, len) = (self.first_moved, self.first_ttt, self.len);
+        self.len = 0;
+        self.first_moved = 0;
+        self.first_ttt = 0;
+        unsafe {
+            // Drop Us
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                ptr.cast::<U>(),
+                first_moved,
+            ));
+            // Drop Ts
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                ptr.add(first_ttt),
+                len - first_ttt,
+            ));
+        }
+        // The buffer is released by the vector
+    }
+}
+
+fn assert_same_layout<T, U>() {
     // It would be nice to assert that statically. We could use a trait that indicates the
     // invariant but this would have two drawbacks:
     //
@@ -49,148 +603,166 @@ where
     // Side note: those runtime assertions are optimised statically: either code without
     // assertion code (the happy path), or pure panic (the incorrect path).
     assert_eq!(
-        std::mem::size_of::<T>(),
-        std::mem::size_of::<U>(),
+        core::mem::size_of::<T>(),
+        core::mem::size_of::<U>(),
         "size_of {} vs {}",
         type_name::<T>(),
         type_name::<U>()
     );
     assert_eq!(
-        std::mem::align_of::<T>(),
-        std::mem::align_of::<U>(),
+        core::mem::align_of::<T>(),
+        core::mem::align_of::<U>(),
         "align_of {} vs {}",
         type_name::<T>(),
         type_name::<U>()
     );
+}
 
-    // Let's take control, we know what we're doing
-    let mut manually_drop = ManuallyDrop::new(input);
-    let slice = manually_drop.as_mut_slice();
+/// The state of a slice after a call to [convert_slice_in_place].
+struct SliceConversion<E> {
+    first_moved: usize,
+    first_ttt: usize,
+    outcome: Result<(), E>,
+}
+
+/// Slice being converted, its elements are dropped if the converter panics.
+struct SliceConversionGuard<'a, T, U> {
+    slice: &'a mut [T],
+    first_moved: usize,
+    first_ttt: usize,
+    _phantom: PhantomData<U>,
+}
+
+impl<T, U> Drop for SliceConversionGuard<'_, T, U> {
+    fn drop(&mut self) {
+        clean_on_error::<T, U>(self.slice, self.first_moved, self.first_ttt);
+    }
+}
 
+fn convert_slice_in_place<T, U, C, E>(slice: &mut [T], convert: &mut C) -> SliceConversion<E>
+where
+    C: FnMut(usize, T, Option<&mut U>) -> Result<VecElementConversionResult<U>, E>,
+{
     // From now on, slice is divided into 3 areas:
     //
-    // - 0..first_moved: elements of type U (to be dropped by the panic handler)
+    // - 0..first_moved: elements of type U (to be dropped by the guard)
     // - first_moved..first_ttt: dropped elements
-    // - first_ttt..: elements of type T (to be dropped by the panic handler)
+    // - first_ttt..: elements of type T (to be dropped by the guard)
     //
-    // This must remain true until the end so that the panic handler drops elements correctly.
-    let mut first_moved = 0;
-    let mut first_ttt = 0;
-
-    let maybe_panic =
-        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| -> Result<(), E> {
-            while first_ttt < slice.len() {
-                // Bring one T back into auto-drop land
-                let ttt = {
-                    let mut ttt = MaybeUninit::<T>::uninit();
-                    unsafe {
-                        std::ptr::copy_nonoverlapping(&slice[first_ttt], ttt.as_mut_ptr(), 1);
-                    }
-                    // The element in the slice is now moved
-                    first_ttt += 1;
-                    unsafe { ttt.assume_init() }
-                };
-
-                // Convert it
-                let converted = convert(
-                    ttt,
-                    // Pass a mutable reference on the preceeding converted element if it exists
-                    if first_moved > 0 {
-                        Some(unsafe { &mut *(&mut slice[first_moved - 1] as *mut T).cast() })
-                    } else {
-                        None
-                    },
-                )?;
-
-                // Store the result
-                match converted {
-                    VecElementConversionResult::Converted(uuu) => {
-                        unsafe {
-                            std::ptr::write((&mut slice[first_moved] as *mut T).cast(), uuu);
-                        }
-                        // The element is now converted
-                        first_moved += 1;
-                    }
-                    VecElementConversionResult::Abandonned => {
-                        // The element has been abandonned by the converter
-                    }
-                }
-            }
-            Ok(())
-        }));
+    // This must remain true until the end so that the guard drops elements correctly.
+    let mut guard = SliceConversionGuard::<T, U> {
+        slice,
+        first_moved: 0,
+        first_ttt: 0,
+        _phantom: PhantomData,
+    };
 
-    fn clean_on_error<T, U>(slice: &mut [T], first_moved: usize, first_ttt: usize) {
-        // Bring Us back into auto-drop land
-        for element in &slice[0..first_moved] {
-            let mut uuu = MaybeUninit::<U>::uninit();
-            unsafe {
-                std::ptr::copy_nonoverlapping(&*(element as *const T).cast(), uuu.as_mut_ptr(), 1);
-                uuu.assume_init();
-            }
+    let outcome = loop {
+        if guard.first_ttt >= guard.slice.len() {
+            break Ok(());
         }
-        // Bring Ts back into auto-drop land
-        for element in &slice[first_ttt..slice.len()] {
+
+        // Bring one T back into auto-drop land
+        let index = guard.first_ttt;
+        let ttt = {
             let mut ttt = MaybeUninit::<T>::uninit();
             unsafe {
-                std::ptr::copy_nonoverlapping(element, ttt.as_mut_ptr(), 1);
-                ttt.assume_init();
+                core::ptr::copy_nonoverlapping(&guard.slice[index], ttt.as_mut_ptr(), 1);
             }
-        }
-    }
+            // The element in the slice is now moved
+            guard.first_ttt += 1;
+            unsafe { ttt.assume_init() }
+        };
 
-    match maybe_panic {
-        Ok(Ok(())) => {
-            unsafe {
-                manually_drop.set_len(first_moved);
+        // Convert it
+        let converted = match convert(
+            index,
+            ttt,
+            // Pass a mutable reference on the preceeding converted element if it exists
+            if guard.first_moved > 0 {
+                Some(unsafe { &mut *(&mut guard.slice[guard.first_moved - 1] as *mut T).cast() })
+            } else {
+                None
+            },
+        ) {
+            Ok(converted) => converted,
+            Err(err) => break Err(err),
+        };
+
+        // Store the result
+        match converted {
+            VecElementConversionResult::Converted(uuu) => {
+                unsafe {
+                    core::ptr::write((&mut guard.slice[guard.first_moved] as *mut T).cast(), uuu);
+                }
+                // The element is now converted
+                guard.first_moved += 1;
+            }
+            VecElementConversionResult::Abandonned => {
+                // The element has been abandonned by the converter
             }
-            Ok(unsafe {
-                std::mem::transmute::<Vec<T>, Vec<U>>(ManuallyDrop::into_inner(manually_drop))
-            })
         }
-        Ok(Err(err)) => {
-            clean_on_error::<T, U>(slice, first_moved, first_ttt);
-            Err(err)
+    };
+
+    let conversion = SliceConversion {
+        first_moved: guard.first_moved,
+        first_ttt: guard.first_ttt,
+        outcome,
+    };
+    // No panic, the caller is now responsible for the elements
+    core::mem::forget(guard);
+    conversion
+}
+
+fn clean_on_error<T, U>(slice: &mut [T], first_moved: usize, first_ttt: usize) {
+    // Bring Us back into auto-drop land
+    for element in &slice[0..first_moved] {
+        let mut uuu = MaybeUninit::<U>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(&*(element as *const T).cast(), uuu.as_mut_ptr(), 1);
+            uuu.assume_init();
         }
-        Err(err) => {
-            clean_on_error::<T, U>(slice, first_moved, first_ttt);
-            panic!("{:?}", err);
+    }
+    // Bring Ts back into auto-drop land
+    for element in &slice[first_ttt..slice.len()] {
+        let mut ttt = MaybeUninit::<T>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(element, ttt.as_mut_ptr(), 1);
+            ttt.assume_init();
         }
     }
 }
 
+/// Splits a vector into its buffer, which does not own any element anymore, and the pointer to
+/// and the number of its elements.
+///
+/// The elements must be accessed via the pointer only, and only as long as the buffer is alive.
+#[cfg(feature = "alloc")]
+fn take_elements<T>(mut input: Vec<T>) -> (Vec<T>, *mut T, usize) {
+    let len = input.len();
+    unsafe {
+        input.set_len(0);
+    }
+    let ptr = input.as_mut_ptr();
+    (input, ptr, len)
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
-    use std::sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        vec,
+        vec::Vec,
     };
 
     use super::*;
+    use crate::test_util::{CountDrop1, CountDrop1000};
 
-    struct CountDrop1 {
-        value: usize,
-        dropped: Arc<AtomicUsize>,
-    }
-
-    impl Drop for CountDrop1 {
-        fn drop(&mut self) {
-            self.dropped.fetch_add(1, Ordering::Relaxed);
-        }
-    }
-
-    struct CountDrop1000 {
-        #[allow(unused)]
-        value: usize,
-        dropped: Arc<AtomicUsize>,
-    }
-
-    impl Drop for CountDrop1000 {
-        fn drop(&mut self) {
-            self.dropped.fetch_add(1000, Ordering::Relaxed);
-        }
-    }
-
+    #[cfg(feature = "alloc")]
     #[test]
     fn test_drop_all_input_and_reduced_output() {
         let dropped1 = Arc::new(AtomicUsize::new(0));
@@ -226,6 +798,7 @@ mod tests {
         assert_eq!(dropped2.load(Ordering::Relaxed), 8000);
     }
 
+    #[cfg(feature = "alloc")]
     #[test]
     fn test_drops_on_error() {
         let dropped1 = Arc::new(AtomicUsize::new(0));
@@ -263,6 +836,7 @@ mod tests {
         assert_eq!(dropped2.load(Ordering::Relaxed), 6000);
     }
 
+    #[cfg(feature = "alloc")]
     #[test]
     fn test_drops_on_panic() {
         let dropped1 = Arc::new(AtomicUsize::new(0));
@@ -298,4 +872,598 @@ mod tests {
         // All 6 (only) converted 2s are dropped
         assert_eq!(dropped2.load(Ordering::Relaxed), 6000);
     }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_mut_stateful_converter() {
+        let mut seen = std::collections::BTreeSet::new();
+        let mut abandonned = 0;
+        let output = convert_vec_in_place_mut::<u32, i32, _>(vec![3, 1, 3, 2, 1, 4], |value, _| {
+            if seen.insert(value) {
+                VecElementConversionResult::Converted(-(value as i32))
+            } else {
+                abandonned += 1;
+                VecElementConversionResult::Abandonned
+            }
+        });
+        assert_eq!(output, vec![-3, -1, -2, -4]);
+        assert_eq!(abandonned, 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_indexed_drops_on_error() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..32 {
+            input.push(CountDrop1 {
+                value: value * 2,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let mut indices = Vec::new();
+        let err = try_convert_vec_in_place_indexed::<CountDrop1, CountDrop1000, _, _>(
+            input,
+            |index, rec, _| {
+                assert_eq!(rec.value, index * 2);
+                indices.push(index);
+                if index == 23 {
+                    Err(index)
+                } else if index % 4 == 0 {
+                    Ok(VecElementConversionResult::Converted(CountDrop1000 {
+                        value: rec.value,
+                        dropped: dropped2.clone(),
+                    }))
+                } else {
+                    Ok(VecElementConversionResult::Abandonned)
+                }
+            },
+        );
+        assert!(matches!(err, Err(23)));
+        assert_eq!(indices, (0..24).collect::<Vec<_>>());
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 32);
+
+        // All 6 (only) converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 6000);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_vec_deque_drop_all_input_and_reduced_output() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        // Make the ring buffer wrap around
+        let mut input = VecDeque::with_capacity(32);
+        for value in 16..32 {
+            input.push_back(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+        for value in (0..16).rev() {
+            input.push_front(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let output = convert_vec_deque_in_place::<CountDrop1, CountDrop1000, _>(input, |rec, _| {
+            if rec.value % 4 == 0 {
+                VecElementConversionResult::Converted(CountDrop1000 {
+                    value: rec.value,
+                    dropped: dropped2.clone(),
+                })
+            } else {
+                VecElementConversionResult::Abandonned
+            }
+        });
+
+        assert_eq!(
+            output.iter().map(|rec| rec.value).collect::<Vec<_>>(),
+            vec![0, 4, 8, 12, 16, 20, 24, 28]
+        );
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 32);
+
+        drop(output);
+
+        // All 8 converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 8000);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_boxed_slice_keeps_allocation() {
+        let input = (0..32u32).collect::<Vec<_>>().into_boxed_slice();
+        let input_ptr = input.as_ptr() as usize;
+
+        let output = convert_boxed_slice_in_place::<u32, i32, _>(input, |value, _| {
+            VecElementConversionResult::Converted(value as i32 * 2)
+        });
+
+        assert_eq!(output.as_ptr() as usize, input_ptr);
+        assert_eq!(output.len(), 32);
+        assert_eq!(output[31], 62);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_boxed_slice_drops_on_error() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..32 {
+            input.push(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let err = try_convert_boxed_slice_in_place::<CountDrop1, CountDrop1000, _, _>(
+            input.into_boxed_slice(),
+            |rec, _| {
+                if rec.value == 23 {
+                    Err(())
+                } else if rec.value % 4 == 0 {
+                    Ok(VecElementConversionResult::Converted(CountDrop1000 {
+                        value: rec.value,
+                        dropped: dropped2.clone(),
+                    }))
+                } else {
+                    Ok(VecElementConversionResult::Abandonned)
+                }
+            },
+        );
+        assert!(err.is_err());
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 32);
+
+        // All 6 (only) converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 6000);
+    }
+
+    #[test]
+    fn test_array_drop_all_input_and_output() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let input = [0, 1, 2, 3].map(|value| CountDrop1 {
+            value,
+            dropped: dropped1.clone(),
+        });
+
+        let output =
+            convert_array_in_place::<CountDrop1, CountDrop1000, _, 4>(input, |rec, prev| {
+                CountDrop1000 {
+                    value: rec.value + prev.map_or(0, |prev| prev.value),
+                    dropped: dropped2.clone(),
+                }
+            });
+
+        assert_eq!(
+            output.iter().map(|rec| rec.value).collect::<Vec<_>>(),
+            vec![0, 1, 3, 6]
+        );
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 4);
+
+        drop(output);
+
+        // All 4 converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 4000);
+    }
+
+    #[test]
+    fn test_array_drops_on_panic() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let input = [0, 1, 2, 3, 4, 5, 6, 7].map(|value| CountDrop1 {
+            value,
+            dropped: dropped1.clone(),
+        });
+
+        let panic = std::panic::catch_unwind(|| {
+            convert_array_in_place::<CountDrop1, CountDrop1000, _, 8>(input, |rec, _| {
+                if rec.value == 5 {
+                    panic!("boom");
+                }
+                CountDrop1000 {
+                    value: rec.value,
+                    dropped: dropped2.clone(),
+                }
+            })
+        });
+        assert!(panic.is_err());
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 8);
+
+        // All 5 (only) converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 5000);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_converter_drop_all_input_and_reduced_output() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..32 {
+            input.push(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let mut converter = VecInPlaceConverter::<CountDrop1, CountDrop1000>::new(input);
+        assert_eq!(converter.remaining(), 32);
+        assert_eq!(converter.available(), 0);
+        while let Some(rec) = converter.next() {
+            if rec.value % 4 == 0 {
+                converter.push(CountDrop1000 {
+                    value: rec.value,
+                    dropped: dropped2.clone(),
+                });
+            }
+        }
+        assert_eq!(converter.remaining(), 0);
+        assert_eq!(converter.available(), 24);
+
+        let output = converter.finish();
+
+        assert_eq!(output.len(), 8);
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 32);
+
+        drop(output);
+
+        // All 8 converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 8000);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_converter_multiple_outputs_per_input() {
+        let mut converter = VecInPlaceConverter::<u32, i32>::new(vec![0, 1, 2, 3, 4, 5]);
+        while let Some(value) = converter.next() {
+            // Duplicate every element as long as abandonned slots allow it
+            if value % 2 == 1 && converter.available() >= 2 {
+                converter.push(value as i32);
+                converter.push(-(value as i32));
+            } else if value % 2 == 1 {
+                converter.push(value as i32);
+            }
+        }
+        assert_eq!(converter.finish(), vec![1, -1, 3, -3, 5, -5]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flat_map() {
+        let mut seen = 0;
+        let output = convert_vec_in_place_flat_map::<u32, i32, _, _>(
+            vec![0, 1, 2, 3, 4, 5, 6, 7],
+            |value| {
+                seen += 1;
+                // 0 and 1 are abandonned, making room for 3 elements from 2
+                match value {
+                    0 | 1 => vec![],
+                    2 => vec![2, 20, 200],
+                    _ => vec![value as i32],
+                }
+            },
+        );
+        assert_eq!(seen, 8);
+        assert_eq!(output, vec![2, 20, 200, 3, 4, 5, 6, 7]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flat_map_drops_on_error() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..32 {
+            input.push(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let err =
+            try_convert_vec_in_place_flat_map::<CountDrop1, CountDrop1000, _, _, _>(input, |rec| {
+                if rec.value == 23 {
+                    Err(())
+                } else if rec.value % 4 == 3 {
+                    Ok(vec![
+                        CountDrop1000 {
+                            value: rec.value,
+                            dropped: dropped2.clone(),
+                        },
+                        CountDrop1000 {
+                            value: rec.value,
+                            dropped: dropped2.clone(),
+                        },
+                    ])
+                } else {
+                    Ok(vec![])
+                }
+            });
+        assert!(err.is_err());
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 32);
+
+        // The 2 2s emitted for each of 3, 7, 11, 15 and 19 are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 10000);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flat_map_drops_on_panic() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..4 {
+            input.push(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let panic = std::panic::catch_unwind(|| {
+            // The first element cannot emit 2 elements since no slot has been abandonned
+            convert_vec_in_place_flat_map::<CountDrop1, CountDrop1000, _, _>(input, |rec| {
+                vec![
+                    CountDrop1000 {
+                        value: rec.value,
+                        dropped: dropped2.clone(),
+                    },
+                    CountDrop1000 {
+                        value: rec.value,
+                        dropped: dropped2.clone(),
+                    },
+                ]
+            })
+        });
+        assert!(panic.is_err());
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 4);
+
+        // Both 2s are dropped, the pushed one and the rejected one
+        assert_eq!(dropped2.load(Ordering::Relaxed), 2000);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_converter_drops_when_finished_early() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..32 {
+            input.push(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let mut converter = VecInPlaceConverter::<CountDrop1, CountDrop1000>::new(input);
+        for rec in converter.by_ref().take(10) {
+            drop(rec);
+        }
+        for value in 0..10 {
+            converter.push(CountDrop1000 {
+                value,
+                dropped: dropped2.clone(),
+            });
+        }
+
+        // The 10 pulled 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 10);
+
+        let output = converter.finish();
+
+        assert_eq!(output.len(), 10);
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 32);
+
+        drop(output);
+
+        // All 10 converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 10000);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_converter_drops_when_dropped_early() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..32 {
+            input.push(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let mut converter = VecInPlaceConverter::<CountDrop1, CountDrop1000>::new(input);
+        for _ in 0..23 {
+            let rec = converter.next().unwrap();
+            if rec.value % 4 == 0 {
+                converter.push(CountDrop1000 {
+                    value: rec.value,
+                    dropped: dropped2.clone(),
+                });
+            }
+        }
+        assert_eq!(converter.last_mut().map(|rec| rec.value), Some(20));
+
+        drop(converter);
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 32);
+
+        // All 6 (only) converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 6000);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_converter_push_without_available_slot() {
+        let mut converter = VecInPlaceConverter::<u32, i32>::new(vec![1, 2, 3]);
+        assert_eq!(converter.try_push(-1), Err(-1));
+        assert_eq!(converter.next(), Some(1));
+        assert_eq!(converter.try_push(-1), Ok(()));
+        assert_eq!(converter.try_push(-2), Err(-2));
+
+        let panic = std::panic::catch_unwind(move || converter.push(-2));
+        assert!(panic.is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_drop_all_input_and_reduced_output() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..1024 {
+            input.push(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let output = par_convert_vec_in_place::<CountDrop1, CountDrop1000, _>(input, |rec| {
+            if rec.value % 4 == 0 {
+                VecElementConversionResult::Converted(CountDrop1000 {
+                    value: rec.value,
+                    dropped: dropped2.clone(),
+                })
+            } else {
+                VecElementConversionResult::Abandonned
+            }
+        });
+
+        assert_eq!(output.len(), 256);
+
+        // Order is preserved
+        for (index, rec) in output.iter().enumerate() {
+            assert_eq!(rec.value, index * 4);
+        }
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 1024);
+
+        drop(output);
+
+        // All 256 converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 256000);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_drops_on_error() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..1024 {
+            input.push(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let converted = AtomicUsize::new(0);
+
+        let err = std::panic::catch_unwind(|| {
+            try_par_convert_vec_in_place::<CountDrop1, CountDrop1000, _, _>(input, |rec| {
+                if rec.value == 523 {
+                    Err(rec.value)
+                } else if rec.value % 4 == 0 {
+                    converted.fetch_add(1, Ordering::Relaxed);
+                    Ok(VecElementConversionResult::Converted(CountDrop1000 {
+                        value: rec.value,
+                        dropped: dropped2.clone(),
+                    }))
+                } else {
+                    Ok(VecElementConversionResult::Abandonned)
+                }
+            })
+        })
+        .unwrap();
+        assert!(matches!(err, Err(523)));
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 1024);
+
+        // All converted 2s are dropped
+        assert_eq!(
+            dropped2.load(Ordering::Relaxed),
+            converted.load(Ordering::Relaxed) * 1000
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_drops_on_panic() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut input = Vec::new();
+        for value in 0..1024 {
+            input.push(CountDrop1 {
+                value,
+                dropped: dropped1.clone(),
+            });
+        }
+
+        let converted = AtomicUsize::new(0);
+
+        let panic = std::panic::catch_unwind(|| {
+            par_convert_vec_in_place::<CountDrop1, CountDrop1000, _>(input, |rec| {
+                if rec.value == 523 {
+                    panic!("boom");
+                } else if rec.value % 4 == 0 {
+                    converted.fetch_add(1, Ordering::Relaxed);
+                    VecElementConversionResult::Converted(CountDrop1000 {
+                        value: rec.value,
+                        dropped: dropped2.clone(),
+                    })
+                } else {
+                    VecElementConversionResult::Abandonned
+                }
+            })
+        });
+        assert!(panic.is_err());
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 1024);
+
+        // All converted 2s are dropped
+        assert_eq!(
+            dropped2.load(Ordering::Relaxed),
+            converted.load(Ordering::Relaxed) * 1000
+        );
+    }
 }
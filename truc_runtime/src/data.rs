@@ -1,6 +1,9 @@
-use std::mem::MaybeUninit;
+use core::mem::MaybeUninit;
 
 /// Internal data holder, heavily unsage, do not use it directly.
+///
+/// It is `Copy` so that records made only of `Copy` datums can be `Copy` as well.
+#[derive(Clone, Copy)]
 pub struct RecordMaybeUninit<const CAP: usize> {
     data: [MaybeUninit<u8>; CAP],
 }
@@ -9,7 +12,7 @@ impl<const CAP: usize> RecordMaybeUninit<CAP> {
     /// Constructs an uninitialized record.
     pub fn new() -> Self {
         Self {
-            data: unsafe { std::mem::MaybeUninit::uninit().assume_init() },
+            data: unsafe { core::mem::MaybeUninit::uninit().assume_init() },
         }
     }
 
@@ -20,7 +23,8 @@ impl<const CAP: usize> RecordMaybeUninit<CAP> {
     /// This function should not be called by anything but truc-generated code. It is used to put
     /// data written by [`Self::write`] back in a droppable state.
     pub unsafe fn read<T>(&self, offset: usize) -> T {
-        std::ptr::read((self.data.as_ptr().add(offset) as *const u8).cast())
+        self.check_access::<T>(offset);
+        core::ptr::read((self.data.as_ptr().add(offset) as *const u8).cast())
     }
 
     /// Stores an object of type `T` in the record at offset `offset`.
@@ -30,7 +34,8 @@ impl<const CAP: usize> RecordMaybeUninit<CAP> {
     /// This function should not be called by anything but truc-generated code which is also
     /// responsible for dropping the data by reading the object (see [`Self::read`]).
     pub unsafe fn write<T>(&mut self, offset: usize, t: T) {
-        std::ptr::write((self.data.as_ptr().add(offset) as *mut u8).cast(), t);
+        self.check_access::<T>(offset);
+        core::ptr::write((self.data.as_ptr().add(offset) as *mut u8).cast(), t);
     }
 
     /// Gets a reference to object of type `T` from the record at offset `offset`.
@@ -39,6 +44,7 @@ impl<const CAP: usize> RecordMaybeUninit<CAP> {
     ///
     /// This function should not be called by anything but truc-generated code.
     pub unsafe fn get<T>(&self, offset: usize) -> &T {
+        self.check_access::<T>(offset);
         &*(self.data.as_ptr().add(offset) as *mut u8).cast()
     }
 
@@ -48,8 +54,59 @@ impl<const CAP: usize> RecordMaybeUninit<CAP> {
     ///
     /// This function should not be called by anything but truc-generated code.
     pub unsafe fn get_mut<T>(&mut self, offset: usize) -> &mut T {
+        self.check_access::<T>(offset);
         &mut *(self.data.as_ptr().add(offset) as *mut u8).cast()
     }
+
+    /// Copies the first `len` bytes of another record, whatever its capacity.
+    ///
+    /// # Safety
+    ///
+    /// This function should not be called by anything but truc-generated code which is also
+    /// responsible for not dropping the data of `other` a second time.
+    pub unsafe fn copy_from<const OTHER_CAP: usize>(
+        &mut self,
+        other: &RecordMaybeUninit<OTHER_CAP>,
+        len: usize,
+    ) {
+        if cfg!(any(debug_assertions, feature = "checked")) {
+            assert!(
+                len <= CAP && len <= OTHER_CAP,
+                "Copy of {} bytes is out of bounds of records with capacities {} and {}",
+                len,
+                OTHER_CAP,
+                CAP
+            );
+        }
+        core::ptr::copy_nonoverlapping(other.data.as_ptr(), self.data.as_mut_ptr(), len);
+    }
+
+    /// Checks that an object of type `T` at offset `offset` is within bounds and properly
+    /// aligned.
+    ///
+    /// The checks are only performed with debug assertions or the `checked` feature enabled.
+    #[inline]
+    fn check_access<T>(&self, offset: usize) {
+        if cfg!(any(debug_assertions, feature = "checked")) {
+            let size = core::mem::size_of::<T>();
+            assert!(
+                offset.checked_add(size).map_or(false, |end| end <= CAP),
+                "Access to {} (size {}) at offset {} is out of bounds of record with capacity {}",
+                core::any::type_name::<T>(),
+                size,
+                offset,
+                CAP
+            );
+            let align = core::mem::align_of::<T>();
+            assert!(
+                (self.data.as_ptr() as usize + offset) % align == 0,
+                "Access to {} (align {}) at offset {} is misaligned",
+                core::any::type_name::<T>(),
+                align,
+                offset
+            );
+        }
+    }
 }
 
 impl<const CAP: usize> Default for RecordMaybeUninit<CAP> {
@@ -75,7 +132,10 @@ mod tests {
     fn test_record_getters() {
         const CAP: usize = std::mem::size_of::<u32>() * 2;
 
-        let mut record = RecordMaybeUninit::<CAP>::default();
+        #[repr(align(4))]
+        struct Aligned(RecordMaybeUninit<CAP>);
+
+        let Aligned(ref mut record) = Aligned(RecordMaybeUninit::<CAP>::default());
         for (i1, i2) in [
             (0x2cfed605, 0xa93696d0),
             (0xf62c11c5, 0xca28ccda),
@@ -125,4 +185,57 @@ mod tests {
         let counter = unsafe { COUNTER };
         assert_eq!(counter, 1);
     }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "checked"))]
+    #[should_panic(expected = "is out of bounds of record with capacity 6")]
+    fn test_record_out_of_bounds() {
+        #[repr(align(4))]
+        struct Aligned(RecordMaybeUninit<6>);
+
+        let Aligned(ref mut record) = Aligned(RecordMaybeUninit::new());
+        unsafe {
+            record.write::<u32>(4, 42);
+        }
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "checked"))]
+    #[should_panic(expected = "at offset 2 is misaligned")]
+    fn test_record_misaligned() {
+        #[repr(align(4))]
+        struct Aligned(RecordMaybeUninit<8>);
+
+        let Aligned(ref record) = Aligned(RecordMaybeUninit::new());
+        unsafe {
+            record.get::<u32>(2);
+        }
+    }
+
+    #[test]
+    fn test_record_copy_from() {
+        #[repr(align(4))]
+        struct Aligned<const CAP: usize>(RecordMaybeUninit<CAP>);
+
+        let Aligned(ref mut small) = Aligned(RecordMaybeUninit::<4>::new());
+        let Aligned(ref mut large) = Aligned(RecordMaybeUninit::<12>::new());
+        unsafe {
+            small.write::<u32>(0, 0x2cfed605);
+            large.copy_from(small, 4);
+            assert_eq!(large.read::<u32>(0), 0x2cfed605);
+        }
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "checked"))]
+    #[should_panic(
+        expected = "Copy of 8 bytes is out of bounds of records with capacities 4 and 12"
+    )]
+    fn test_record_copy_from_out_of_bounds() {
+        let small = RecordMaybeUninit::<4>::new();
+        let mut large = RecordMaybeUninit::<12>::new();
+        unsafe {
+            large.copy_from(&small, 8);
+        }
+    }
 }
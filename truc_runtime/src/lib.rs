@@ -1,4 +1,22 @@
 #![cfg_attr(coverage_nightly, feature(coverage_attribute))]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+// The test harness links the standard library anyway
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+#[cfg(all(feature = "rayon", not(feature = "std")))]
+compile_error!("The rayon feature requires the std feature");
 
 pub mod convert;
 pub mod data;
+#[cfg(feature = "alloc")]
+pub mod pool;
+#[cfg(feature = "alloc")]
+pub mod ring;
+#[cfg(feature = "alloc")]
+mod storage;
+#[cfg(test)]
+mod test_util;
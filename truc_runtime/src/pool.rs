@@ -0,0 +1,362 @@
+//! Pool of fixed size record slots.
+//!
+//! All the variants of a truc record have the same size and alignment, which makes it possible to
+//! store them in slots of a single storage type `S`, typically the generated
+//! `RecordUninitialized<{ MAX_SIZE }>`, and to convert a record to its next variant in its slot.
+
+use alloc::vec::Vec;
+use core::{
+    any::type_name,
+    convert::Infallible,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::storage::{assert_fits, drop_record};
+
+static NEXT_POOL_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Pool of record slots of storage type `S`.
+///
+/// Records are inserted in free slots, or in new slots if none is free, and are referenced by
+/// [RecordHandle]s. Removing a record frees its slot so that it can be reused by another record.
+///
+/// Records still in the pool are dropped when the pool is dropped.
+///
+/// Since any type of record can be inserted, the pool is neither [Send] nor [Sync].
+///
+/// ```rust
+/// # use truc_runtime::pool::RecordPool;
+/// #[repr(align(8))]
+/// struct Storage([u8; 24]);
+///
+/// let mut pool = RecordPool::<Storage>::new();
+/// let handle = pool.insert(String::from("42"));
+/// assert_eq!(pool.get(&handle), "42");
+/// let handle = pool.convert(handle, |s| s.parse::<u64>().unwrap());
+/// assert_eq!(*pool.get(&handle), 42);
+/// assert_eq!(pool.remove(handle), 42);
+/// assert!(pool.is_empty());
+/// ```
+pub struct RecordPool<S> {
+    id: usize,
+    slots: Vec<Slot<S>>,
+    free: Vec<usize>,
+    _not_send_sync: PhantomData<*mut ()>,
+}
+
+struct Slot<S> {
+    storage: MaybeUninit<S>,
+    drop: Option<unsafe fn(*mut u8)>,
+}
+
+/// Handle of a record of type `R` stored in a [RecordPool].
+///
+/// A handle cannot be cloned, it is consumed when the record is removed from the pool or
+/// converted to another type.
+pub struct RecordHandle<R> {
+    pool_id: usize,
+    index: usize,
+    _phantom: PhantomData<fn() -> R>,
+}
+
+impl<R> RecordHandle<R> {
+    /// Gets the index of the slot holding the record.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<S> RecordPool<S> {
+    /// Constructs an empty pool.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Constructs an empty pool with room for at least `capacity` slots.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            id: NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed),
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            _not_send_sync: PhantomData,
+        }
+    }
+
+    /// Gets the number of records stored in the pool.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    /// Tells whether the pool does not contain any record.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of allocated slots, occupied or free.
+    pub fn slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Inserts a record in a free slot.
+    ///
+    /// It panics if `R` does not fit in `S` (size or alignment).
+    pub fn insert<R>(&mut self, record: R) -> RecordHandle<R> {
+        assert_fits::<R, S>();
+        let index = if let Some(index) = self.free.pop() {
+            index
+        } else {
+            self.slots.push(Slot {
+                storage: MaybeUninit::uninit(),
+                drop: None,
+            });
+            self.slots.len() - 1
+        };
+        let slot = &mut self.slots[index];
+        debug_assert!(slot.drop.is_none());
+        unsafe { core::ptr::write(slot.storage.as_mut_ptr().cast::<R>(), record) };
+        slot.drop = Some(drop_record::<R>);
+        RecordHandle {
+            pool_id: self.id,
+            index,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Gets a reference to the record.
+    ///
+    /// It panics if the handle was not created by this pool.
+    pub fn get<R>(&self, handle: &RecordHandle<R>) -> &R {
+        self.check_handle(handle);
+        unsafe { &*self.slots[handle.index].storage.as_ptr().cast::<R>() }
+    }
+
+    /// Gets a mutable reference to the record.
+    ///
+    /// It panics if the handle was not created by this pool.
+    pub fn get_mut<R>(&mut self, handle: &RecordHandle<R>) -> &mut R {
+        self.check_handle(handle);
+        unsafe { &mut *self.slots[handle.index].storage.as_mut_ptr().cast::<R>() }
+    }
+
+    /// Removes the record from the pool and frees its slot.
+    ///
+    /// It panics if the handle was not created by this pool.
+    pub fn remove<R>(&mut self, handle: RecordHandle<R>) -> R {
+        self.check_handle(&handle);
+        let slot = &mut self.slots[handle.index];
+        slot.drop = None;
+        let record = unsafe { core::ptr::read(slot.storage.as_ptr().cast::<R>()) };
+        self.free.push(handle.index);
+        record
+    }
+
+    /// Converts the record to a record of type `Q` in its slot, typically a record of the next
+    /// variant.
+    ///
+    /// If the converter panics then the slot is freed and your memory is safe.
+    ///
+    /// It panics if the handle was not created by this pool or if `Q` does not fit in `S`.
+    pub fn convert<R, Q, C>(&mut self, handle: RecordHandle<R>, convert: C) -> RecordHandle<Q>
+    where
+        C: FnOnce(R) -> Q,
+    {
+        match self.try_convert(handle, |record| -> Result<Q, Infallible> {
+            Ok(convert(record))
+        }) {
+            Ok(handle) => handle,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Converts the record to a record of type `Q` in its slot, typically a record of the next
+    /// variant.
+    ///
+    /// If the converter fails or panics then the slot is freed and your memory is safe.
+    ///
+    /// It panics if the handle was not created by this pool or if `Q` does not fit in `S`.
+    pub fn try_convert<R, Q, C, E>(
+        &mut self,
+        handle: RecordHandle<R>,
+        convert: C,
+    ) -> Result<RecordHandle<Q>, E>
+    where
+        C: FnOnce(R) -> Result<Q, E>,
+    {
+        assert_fits::<Q, S>();
+        self.check_handle(&handle);
+        let index = handle.index;
+        let slot = &mut self.slots[index];
+        // The slot is vacant until the converted record is written back
+        slot.drop = None;
+        let record = unsafe { core::ptr::read(slot.storage.as_ptr().cast::<R>()) };
+
+        let guard = FreeSlotGuard {
+            free: &mut self.free,
+            index,
+        };
+        let converted = convert(record)?;
+        core::mem::forget(guard);
+
+        let slot = &mut self.slots[index];
+        unsafe { core::ptr::write(slot.storage.as_mut_ptr().cast::<Q>(), converted) };
+        slot.drop = Some(drop_record::<Q>);
+        Ok(RecordHandle {
+            pool_id: self.id,
+            index,
+            _phantom: PhantomData,
+        })
+    }
+
+    fn check_handle<R>(&self, handle: &RecordHandle<R>) {
+        assert_eq!(
+            handle.pool_id,
+            self.id,
+            "Handle of {} does not belong to this pool",
+            type_name::<R>()
+        );
+    }
+}
+
+impl<S> Default for RecordPool<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Drop for RecordPool<S> {
+    fn drop(&mut self) {
+        for slot in &mut self.slots {
+            if let Some(drop) = slot.drop.take() {
+                unsafe { drop(slot.storage.as_mut_ptr().cast()) };
+            }
+        }
+    }
+}
+
+/// Frees the slot unless forgotten, i.e. when the converter fails.
+struct FreeSlotGuard<'a> {
+    free: &'a mut Vec<usize>,
+    index: usize,
+}
+
+impl<'a> Drop for FreeSlotGuard<'a> {
+    fn drop(&mut self) {
+        self.free.push(self.index);
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::{sync::Arc, vec};
+
+    use super::*;
+    use crate::test_util::{CountDrop1, CountDrop1000};
+
+    #[repr(align(8))]
+    struct Storage {
+        _data: [MaybeUninit<u8>; 24],
+    }
+
+    #[test]
+    fn test_insert_convert_remove_reuses_slots() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let mut pool = RecordPool::<Storage>::new();
+        let handles = (0..8)
+            .map(|value| {
+                pool.insert(CountDrop1 {
+                    value,
+                    dropped: dropped1.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(pool.len(), 8);
+
+        let mut converted = Vec::new();
+        for handle in handles {
+            if pool.get(&handle).value % 2 == 0 {
+                let index = handle.index();
+                let handle = pool.convert(handle, |rec| CountDrop1000 {
+                    value: rec.value * 10,
+                    dropped: dropped2.clone(),
+                });
+                assert_eq!(handle.index(), index);
+                converted.push(handle);
+            } else {
+                drop(pool.remove(handle));
+            }
+        }
+        assert_eq!(pool.len(), 4);
+        assert_eq!(pool.slots(), 8);
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 8);
+
+        pool.get_mut(&converted[1]).value += 1;
+        assert_eq!(
+            converted
+                .iter()
+                .map(|handle| pool.get(handle).value)
+                .collect::<Vec<_>>(),
+            vec![0, 21, 40, 60]
+        );
+
+        // Free slots are reused
+        let handle = pool.insert(42_u64);
+        assert!(handle.index() < 8);
+        assert_eq!(pool.slots(), 8);
+
+        drop(pool);
+
+        // All 4 converted 2s are dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 4000);
+    }
+
+    #[test]
+    fn test_convert_frees_slot_on_error_and_panic() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+
+        let mut pool = RecordPool::<Storage>::new();
+        let handle = pool.insert(CountDrop1 {
+            value: 1,
+            dropped: dropped1.clone(),
+        });
+        let err = pool.try_convert(handle, |rec| -> Result<u64, _> { Err(rec.value) });
+        assert!(matches!(err, Err(1)));
+        assert!(pool.is_empty());
+
+        let handle = pool.insert(CountDrop1 {
+            value: 2,
+            dropped: dropped1.clone(),
+        });
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            pool.convert(handle, |_rec| -> u64 { panic!("boom") });
+        }));
+        assert!(panic.is_err());
+        assert!(pool.is_empty());
+        assert_eq!(pool.slots(), 1);
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not belong to this pool")]
+    fn test_foreign_handle() {
+        let mut pool1 = RecordPool::<Storage>::new();
+        let pool2 = RecordPool::<Storage>::new();
+        let handle = pool1.insert(42_u64);
+        pool2.get(&handle);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit in")]
+    fn test_too_large_record() {
+        let mut pool = RecordPool::<Storage>::new();
+        pool.insert([0_u64; 4]);
+    }
+}
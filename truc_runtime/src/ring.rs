@@ -0,0 +1,510 @@
+//! Fixed capacity ring buffer of record slots.
+//!
+//! The buffer is shared by one [Producer] and one [Consumer] which can live in different threads.
+//! Slots are of a single storage type `S`, typically the generated
+//! `RecordUninitialized<{ MAX_SIZE }>`, so that records of any variant of a definition can be
+//! pushed, and converted in place by the consumer.
+
+use alloc::{boxed::Box, sync::Arc};
+use core::{
+    any::{type_name, TypeId},
+    cell::{Cell, UnsafeCell},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::storage::{assert_fits, drop_record};
+
+/// Creates a ring buffer of `capacity` slots of storage type `S`.
+///
+/// It panics if `capacity` is zero.
+///
+/// ```rust
+/// # use truc_runtime::ring::ring_buffer;
+/// #[repr(align(8))]
+/// struct Storage([u8; 24]);
+///
+/// let (mut producer, mut consumer) = ring_buffer::<Storage>(2);
+/// let thread = std::thread::spawn(move || {
+///     for i in 0..10 {
+///         let mut record = i.to_string();
+///         while let Err(back) = producer.push(record) {
+///             record = back;
+///             std::thread::yield_now();
+///         }
+///     }
+/// });
+/// let mut sum = 0;
+/// while !consumer.is_disconnected() || !consumer.is_empty() {
+///     if let Some(value) = consumer.convert_front(|s: String| s.parse::<u64>().unwrap()) {
+///         *value *= 2;
+///         sum += consumer.pop::<u64>().unwrap();
+///     } else {
+///         std::thread::yield_now();
+///     }
+/// }
+/// thread.join().unwrap();
+/// assert_eq!(sum, 90);
+/// ```
+pub fn ring_buffer<S>(capacity: usize) -> (Producer<S>, Consumer<S>) {
+    assert!(capacity > 0, "Ring buffer capacity must not be zero");
+    let inner = Arc::new(Inner {
+        slots: (0..capacity)
+            .map(|_| {
+                UnsafeCell::new(Slot {
+                    storage: MaybeUninit::uninit(),
+                    meta: None,
+                })
+            })
+            .collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+        disconnected: AtomicBool::new(false),
+    });
+    (
+        Producer {
+            inner: inner.clone(),
+        },
+        Consumer {
+            inner,
+            _not_sync: PhantomData,
+        },
+    )
+}
+
+struct Inner<S> {
+    slots: Box<[UnsafeCell<Slot<S>>]>,
+    // Position of the next record to pop, only written by the consumer
+    head: AtomicUsize,
+    // Position of the next record to push, only written by the producer
+    tail: AtomicUsize,
+    // Set by the first side being dropped, after its last access to head or tail
+    disconnected: AtomicBool,
+}
+
+// Slots between head and tail are owned by the consumer, the other ones by the producer. Records
+// pushed in the slots are required to be `Send`.
+unsafe impl<S> Send for Inner<S> {}
+unsafe impl<S> Sync for Inner<S> {}
+
+struct Slot<S> {
+    storage: MaybeUninit<S>,
+    meta: Option<SlotMeta>,
+}
+
+#[derive(Clone, Copy)]
+struct SlotMeta {
+    type_id: TypeId,
+    type_name: &'static str,
+    drop: unsafe fn(*mut u8),
+}
+
+impl<S> Inner<S> {
+    fn len(&self) -> usize {
+        self.tail
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.head.load(Ordering::Acquire))
+    }
+
+    fn slot(&self, position: usize) -> *mut Slot<S> {
+        self.slots[position % self.slots.len()].get()
+    }
+}
+
+impl<S> Drop for Inner<S> {
+    fn drop(&mut self) {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let mut position = head;
+        while position != tail {
+            let slot = self.slots[position % self.slots.len()].get_mut();
+            if let Some(meta) = slot.meta.take() {
+                unsafe { (meta.drop)(slot.storage.as_mut_ptr().cast()) };
+            }
+            position = position.wrapping_add(1);
+        }
+    }
+}
+
+/// Producing side of a ring buffer, see [ring_buffer].
+pub struct Producer<S> {
+    inner: Arc<Inner<S>>,
+}
+
+impl<S> Producer<S> {
+    /// Gets the number of slots of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.inner.slots.len()
+    }
+
+    /// Gets the number of records in the buffer.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Tells whether the buffer does not contain any record.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Tells whether all the slots of the buffer are occupied.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Tells whether the consumer has been dropped, in which case nothing will pop records any
+    /// more.
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.disconnected.load(Ordering::Acquire)
+    }
+
+    /// Pushes a record at the back of the buffer if there is a free slot, otherwise gives it back.
+    ///
+    /// It panics if `R` does not fit in `S` (size or alignment).
+    pub fn push<R>(&mut self, record: R) -> Result<(), R>
+    where
+        R: Send + 'static,
+    {
+        assert_fits::<R, S>();
+        let tail = self.inner.tail.load(Ordering::Relaxed);
+        let head = self.inner.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) == self.inner.slots.len() {
+            return Err(record);
+        }
+        // The slot is not between head and tail, it is owned by the producer
+        let slot = unsafe { &mut *self.inner.slot(tail) };
+        unsafe { core::ptr::write(slot.storage.as_mut_ptr().cast::<R>(), record) };
+        slot.meta = Some(SlotMeta::of::<R>());
+        self.inner
+            .tail
+            .store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+}
+
+impl<S> Drop for Producer<S> {
+    fn drop(&mut self) {
+        self.inner.disconnected.store(true, Ordering::Release);
+    }
+}
+
+/// Consuming side of a ring buffer, see [ring_buffer].
+pub struct Consumer<S> {
+    inner: Arc<Inner<S>>,
+    // Records are accessed by reference, they are not required to be `Sync`
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+impl<S> Consumer<S> {
+    /// Gets the number of slots of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.inner.slots.len()
+    }
+
+    /// Gets the number of records in the buffer.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Tells whether the buffer does not contain any record.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Tells whether the producer has been dropped, in which case no more records will be pushed.
+    ///
+    /// Once it returns `true`, all the records pushed by the producer are visible to
+    /// [`is_empty`](Self::is_empty) and [`pop`](Self::pop).
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.disconnected.load(Ordering::Acquire)
+    }
+
+    /// Gets the type name of the record at the front of the buffer.
+    pub fn front_type_name(&self) -> Option<&'static str> {
+        self.front_meta().map(|meta| meta.type_name)
+    }
+
+    /// Tells whether the record at the front of the buffer is of type `R`.
+    pub fn front_is<R>(&self) -> bool
+    where
+        R: 'static,
+    {
+        self.front_meta()
+            .map_or(false, |meta| meta.type_id == TypeId::of::<R>())
+    }
+
+    /// Gets a reference to the record at the front of the buffer.
+    ///
+    /// It panics if the record is not of type `R`.
+    pub fn peek<R>(&self) -> Option<&R>
+    where
+        R: 'static,
+    {
+        let slot = self.front_slot::<R>()?;
+        Some(unsafe { &*(*slot).storage.as_ptr().cast::<R>() })
+    }
+
+    /// Gets a mutable reference to the record at the front of the buffer.
+    ///
+    /// It panics if the record is not of type `R`.
+    pub fn peek_mut<R>(&mut self) -> Option<&mut R>
+    where
+        R: 'static,
+    {
+        let slot = self.front_slot::<R>()?;
+        Some(unsafe { &mut *(*slot).storage.as_mut_ptr().cast::<R>() })
+    }
+
+    /// Pops the record at the front of the buffer.
+    ///
+    /// It panics if the record is not of type `R`.
+    pub fn pop<R>(&mut self) -> Option<R>
+    where
+        R: 'static,
+    {
+        let slot = unsafe { &mut *self.front_slot::<R>()? };
+        slot.meta = None;
+        let record = unsafe { core::ptr::read(slot.storage.as_ptr().cast::<R>()) };
+        self.advance();
+        Some(record)
+    }
+
+    /// Converts the record at the front of the buffer to a record of type `Q` in its slot,
+    /// typically a record of the next variant, and gets a mutable reference to it.
+    ///
+    /// If the converter panics then the record is removed from the buffer and your memory is safe.
+    ///
+    /// It panics if the record is not of type `R` or if `Q` does not fit in `S`.
+    pub fn convert_front<R, Q, C>(&mut self, convert: C) -> Option<&mut Q>
+    where
+        R: 'static,
+        Q: Send + 'static,
+        C: FnOnce(R) -> Q,
+    {
+        assert_fits::<Q, S>();
+        let slot = unsafe { &mut *self.front_slot::<R>()? };
+        // The slot is vacant until the converted record is written back
+        slot.meta = None;
+        let record = unsafe { core::ptr::read(slot.storage.as_ptr().cast::<R>()) };
+
+        let guard = AdvanceGuard { consumer: self };
+        let converted = convert(record);
+        core::mem::forget(guard);
+
+        unsafe { core::ptr::write(slot.storage.as_mut_ptr().cast::<Q>(), converted) };
+        slot.meta = Some(SlotMeta::of::<Q>());
+        Some(unsafe { &mut *slot.storage.as_mut_ptr().cast::<Q>() })
+    }
+
+    fn front_meta(&self) -> Option<SlotMeta> {
+        let head = self.inner.head.load(Ordering::Relaxed);
+        let tail = self.inner.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        // The slot is between head and tail, it is owned by the consumer
+        unsafe { (*self.inner.slot(head)).meta }
+    }
+
+    fn front_slot<R>(&self) -> Option<*mut Slot<S>>
+    where
+        R: 'static,
+    {
+        let meta = self.front_meta()?;
+        assert!(
+            meta.type_id == TypeId::of::<R>(),
+            "Front record is a {}, not a {}",
+            meta.type_name,
+            type_name::<R>()
+        );
+        Some(self.inner.slot(self.inner.head.load(Ordering::Relaxed)))
+    }
+
+    fn advance(&mut self) {
+        let head = self.inner.head.load(Ordering::Relaxed);
+        self.inner
+            .head
+            .store(head.wrapping_add(1), Ordering::Release);
+    }
+}
+
+impl<S> Drop for Consumer<S> {
+    fn drop(&mut self) {
+        self.inner.disconnected.store(true, Ordering::Release);
+    }
+}
+
+/// Removes the front slot unless forgotten, i.e. when the converter panics.
+struct AdvanceGuard<'a, S> {
+    consumer: &'a mut Consumer<S>,
+}
+
+impl<'a, S> Drop for AdvanceGuard<'a, S> {
+    fn drop(&mut self) {
+        self.consumer.advance();
+    }
+}
+
+impl SlotMeta {
+    fn of<R>() -> Self
+    where
+        R: 'static,
+    {
+        Self {
+            type_id: TypeId::of::<R>(),
+            type_name: type_name::<R>(),
+            drop: drop_record::<R>,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::test_util::{CountDrop1, CountDrop1000};
+
+    #[repr(align(8))]
+    struct Storage {
+        _data: [MaybeUninit<u8>; 24],
+    }
+
+    #[test]
+    fn test_push_convert_pop_and_drop_live_records() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+        let dropped2 = Arc::new(AtomicUsize::new(0));
+
+        let (mut producer, mut consumer) = ring_buffer::<Storage>(4);
+        let mut next = 0;
+        // Go around the buffer a few times
+        for _ in 0..3 {
+            while !producer.is_full() {
+                assert!(producer
+                    .push(CountDrop1 {
+                        value: next,
+                        dropped: dropped1.clone(),
+                    })
+                    .is_ok());
+                next += 1;
+            }
+            // The rejected record is given back, not dropped
+            let before = dropped1.load(Ordering::Relaxed);
+            let rejected = producer
+                .push(CountDrop1 {
+                    value: next,
+                    dropped: dropped1.clone(),
+                })
+                .unwrap_err();
+            assert_eq!(rejected.value, next);
+            assert_eq!(dropped1.load(Ordering::Relaxed), before);
+            drop(rejected);
+            assert_eq!(dropped1.load(Ordering::Relaxed), before + 1);
+            assert!(producer.is_full());
+            assert!(consumer.front_is::<CountDrop1>());
+            let value = consumer
+                .convert_front(|rec: CountDrop1| CountDrop1000 {
+                    value: rec.value * 10,
+                    dropped: dropped2.clone(),
+                })
+                .unwrap()
+                .value;
+            assert_eq!(consumer.peek::<CountDrop1000>().unwrap().value, value);
+            assert_eq!(consumer.pop::<CountDrop1000>().unwrap().value, value);
+            assert_eq!(consumer.len(), 3);
+            consumer.peek_mut::<CountDrop1>().unwrap().value += 1;
+            assert_eq!(consumer.pop::<CountDrop1>().unwrap().value, value / 10 + 2);
+        }
+        assert_eq!(consumer.len(), 2);
+        assert_eq!(next, 8);
+
+        // 6 1s have been dropped, plus the 3 rejected ones
+        assert_eq!(dropped1.load(Ordering::Relaxed), 9);
+        // 3 2s have been dropped
+        assert_eq!(dropped2.load(Ordering::Relaxed), 3000);
+
+        drop(producer);
+        assert!(consumer.is_disconnected());
+        assert_eq!(dropped1.load(Ordering::Relaxed), 9);
+
+        drop(consumer);
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 11);
+    }
+
+    #[test]
+    fn test_convert_front_panic() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+
+        let (mut producer, mut consumer) = ring_buffer::<Storage>(2);
+        for value in 0..2 {
+            assert!(producer
+                .push(CountDrop1 {
+                    value,
+                    dropped: dropped1.clone(),
+                })
+                .is_ok());
+        }
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            consumer.convert_front(|_rec: CountDrop1| -> u64 { panic!("boom") });
+        }));
+        assert!(panic.is_err());
+        assert_eq!(consumer.len(), 1);
+        assert_eq!(dropped1.load(Ordering::Relaxed), 1);
+        assert_eq!(consumer.pop::<CountDrop1>().unwrap().value, 1);
+        assert_eq!(dropped1.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_producer_disconnected() {
+        let (producer, consumer) = ring_buffer::<Storage>(2);
+        assert!(!producer.is_disconnected());
+        drop(consumer);
+        assert!(producer.is_disconnected());
+    }
+
+    #[test]
+    #[should_panic(expected = "Front record is a u64, not a u32")]
+    fn test_pop_wrong_type() {
+        let (mut producer, mut consumer) = ring_buffer::<Storage>(2);
+        assert!(producer.push(42_u64).is_ok());
+        consumer.pop::<u32>();
+    }
+
+    #[test]
+    fn test_threads() {
+        let dropped1 = Arc::new(AtomicUsize::new(0));
+
+        let (mut producer, mut consumer) = ring_buffer::<Storage>(3);
+        let thread = std::thread::spawn({
+            let dropped1 = dropped1.clone();
+            move || {
+                for value in 0..1000 {
+                    let mut record = CountDrop1 {
+                        value,
+                        dropped: dropped1.clone(),
+                    };
+                    while let Err(back) = producer.push(record) {
+                        record = back;
+                        std::thread::yield_now();
+                    }
+                }
+            }
+        });
+        let mut expected = 0;
+        while expected < 1000 {
+            if let Some(rec) = consumer.pop::<CountDrop1>() {
+                assert_eq!(rec.value, expected);
+                expected += 1;
+            } else {
+                std::thread::yield_now();
+            }
+        }
+        thread.join().unwrap();
+        assert!(consumer.is_empty());
+
+        // All 1s are dropped
+        assert_eq!(dropped1.load(Ordering::Relaxed), 1000);
+    }
+}
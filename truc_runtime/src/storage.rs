@@ -0,0 +1,29 @@
+//! Helpers shared by the containers storing records in slots of a storage type `S`.
+
+use core::any::type_name;
+
+/// Drops in place the record of type `R` stored at `ptr`.
+///
+/// # Safety
+///
+/// `ptr` must point to an initialized record of type `R` which is not used any more.
+pub(crate) unsafe fn drop_record<R>(ptr: *mut u8) {
+    core::ptr::drop_in_place(ptr.cast::<R>());
+}
+
+/// Panics if `R` does not fit in `S` (size or alignment).
+pub(crate) fn assert_fits<R, S>() {
+    // Statically optimized by the compiler
+    assert!(
+        core::mem::size_of::<R>() <= core::mem::size_of::<S>(),
+        "{} does not fit in {}",
+        type_name::<R>(),
+        type_name::<S>()
+    );
+    assert!(
+        core::mem::align_of::<R>() <= core::mem::align_of::<S>(),
+        "{} is not aligned enough for {}",
+        type_name::<S>(),
+        type_name::<R>()
+    );
+}
@@ -0,0 +1,31 @@
+//! Test fixtures shared by the runtime modules.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Record counting its drops one by one.
+pub(crate) struct CountDrop1 {
+    pub value: usize,
+    pub dropped: Arc<AtomicUsize>,
+}
+
+impl Drop for CountDrop1 {
+    fn drop(&mut self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Record counting its drops one thousand by one thousand, so that it can share a counter with
+/// [CountDrop1].
+pub(crate) struct CountDrop1000 {
+    pub value: usize,
+    pub dropped: Arc<AtomicUsize>,
+}
+
+impl Drop for CountDrop1000 {
+    fn drop(&mut self) {
+        self.dropped.fetch_add(1000, Ordering::Relaxed);
+    }
+}